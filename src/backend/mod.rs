@@ -17,6 +17,40 @@ use url::{ParseError, Url};
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Backend {
     pub url: Url,
+    pub search: SearchOptions,
+    pub licenses: LicensePolicy,
+}
+
+/// The license policy of a deployment.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct LicensePolicy {
+    /// SPDX license identifiers which are allowed
+    pub allow: Vec<String>,
+    /// SPDX license identifiers which must not be used
+    pub deny: Vec<String>,
+}
+
+/// Options for searching large numbers of packages.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SearchOptions {
+    /// The number of package URLs sent with a single request
+    pub chunk_size: usize,
+    /// The number of requests running in parallel
+    pub concurrency: usize,
+    /// If the backend can search packages by free text
+    pub text: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 500,
+            concurrency: 4,
+            text: false,
+        }
+    }
 }
 
 impl Backend {
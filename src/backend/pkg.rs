@@ -38,6 +38,18 @@ impl PackageService {
         self.batch_to_refs("/api/package", purls).await
     }
 
+    /// Lookup the full package information of each purl, one request at a time.
+    pub async fn lookup_packages<'a, I>(&self, purls: I) -> Result<Vec<Package>, Error>
+    where
+        I: IntoIterator<Item = PackageUrl<'a>>,
+    {
+        let mut result = vec![];
+        for purl in purls {
+            result.push(self.lookup(purl).await?);
+        }
+        Ok(result)
+    }
+
     pub async fn dependencies<'a, I>(&self, purls: I) -> Result<Vec<PackageDependencies>, Error>
     where
         I: IntoIterator<Item = PackageUrl<'a>>,
@@ -59,6 +71,29 @@ impl PackageService {
         self.batch_to_refs("/api/package/search", purls).await
     }
 
+    /// Search packages by free text, like a part of their name.
+    ///
+    /// Only available if the backend announces it with [`super::SearchOptions::text`].
+    ///
+    /// The refinements are pairs of a part of the package URL (`type`, `namespace`, `version` or
+    /// `subpath`) and the value it must have.
+    pub async fn search_text(
+        &self,
+        query: &str,
+        refinements: &[(&str, &str)],
+    ) -> Result<PackageDependents, Error> {
+        Ok(self
+            .client
+            .get(self.backend.url.join("/api/package/search")?)
+            .query(&[("q", query)])
+            .query(refinements)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
     /// common call of getting some refs for a batch of purls
     async fn batch_to_refs<'a, I, R>(&self, path: &str, purls: I) -> Result<R, Error>
     where
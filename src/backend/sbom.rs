@@ -1,15 +1,32 @@
 use super::{Backend, Error};
+use serde::{Deserialize, Serialize};
 use url::Url;
 
+/// A document of a shared SBOM inspection.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedDocument {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Deserialize)]
+struct ShareResponse {
+    id: String,
+}
+
 #[allow(unused)]
 pub struct SBOMService {
     backend: Backend,
+    client: reqwest::Client,
 }
 
 #[allow(unused)]
 impl SBOMService {
     pub fn new(backend: Backend) -> Self {
-        Self { backend }
+        Self {
+            backend,
+            client: reqwest::Client::new(),
+        }
     }
 
     pub fn download_href(&self, pkg: impl AsRef<str>) -> Result<Url, Error> {
@@ -21,4 +38,37 @@ impl SBOMService {
 
         Ok(url)
     }
+
+    /// Store the documents of an inspection, returning the ID to load them again.
+    pub async fn share(&self, documents: &[SharedDocument]) -> Result<String, Error> {
+        let response: ShareResponse = self
+            .client
+            .post(self.backend.url.join("/api/sbom/share")?)
+            .json(documents)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(response.id)
+    }
+
+    /// Load the documents of a shared inspection.
+    pub async fn shared(&self, id: &str) -> Result<Vec<SharedDocument>, Error> {
+        let mut url = self.backend.url.join("/api/sbom/share/")?;
+        url.path_segments_mut()
+            .map_err(|_| url::ParseError::RelativeUrlWithCannotBeABaseBase)?
+            .pop_if_empty()
+            .push(id);
+
+        Ok(self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
 }
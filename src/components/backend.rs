@@ -15,6 +15,10 @@ pub struct BackendProperties {
 #[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
 pub struct BackendEndpoint {
     pub url: Url,
+    #[serde(default)]
+    pub licenses: crate::backend::LicensePolicy,
+    #[serde(default)]
+    pub search: crate::backend::SearchOptions,
 }
 
 #[function_component(Backend)]
@@ -38,7 +42,11 @@ pub fn backend(props: &BackendProperties) -> Html {
 
             log::info!("Found: {endpoint:?}");
 
-            Ok::<_, String>(crate::backend::Backend { url: endpoint.url })
+            Ok::<_, String>(crate::backend::Backend {
+                url: endpoint.url,
+                search: endpoint.search,
+                licenses: endpoint.licenses,
+            })
         },
         UseAsyncOptions::enable_auto(),
     );
@@ -1,22 +1,74 @@
-use crate::{backend::data, components::Trusted, pages::AppRoute};
+use crate::{
+    backend::data,
+    components::{highlight, Trusted},
+    pages::AppRoute,
+    purl::normalize,
+    version,
+};
 use itertools::Itertools;
 use packageurl::PackageUrl;
 use patternfly_yew::prelude::*;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::rc::Rc;
 use std::str::FromStr;
 use yew::prelude::*;
 use yew_nested_router::components::Link;
 
-#[derive(Clone, Debug, PartialEq, Eq, Properties)]
+#[derive(Clone, Debug, PartialEq, Properties)]
 pub struct PackageRefsProperties {
     #[prop_or_default]
     pub refs: Vec<data::PackageRef>,
+    /// The files a (normalized) package URL was found in, adds a "Source file" column if present.
+    #[prop_or_default]
+    pub sources: Option<Rc<BTreeMap<String, Vec<String>>>>,
+    /// The package URLs, as used in the SBOM, of a normalized package URL. Adds a "Package URL
+    /// in SBOM" column if present.
+    #[prop_or_default]
+    pub originals: Option<Rc<BTreeMap<String, Vec<String>>>>,
+    /// Trusted replacements of package URLs, adds a "Suggested replacement" column if present.
+    #[prop_or_default]
+    pub suggestions: Option<Rc<BTreeMap<String, data::PackageRef>>>,
+    /// Called with the source file and package URL when a source file is clicked.
+    #[prop_or_default]
+    pub onselect: Option<Callback<(String, String)>>,
+    /// Text to highlight in the names of the packages.
+    #[prop_or_default]
+    pub highlight: Option<String>,
 }
 
+/// Render the names of the source files, as links if there is a callback for selecting them.
+pub fn render_sources(
+    purl: &str,
+    sources: &[String],
+    onselect: &Option<Callback<(String, String)>>,
+) -> Html {
+    let onselect = match onselect {
+        Some(onselect) => onselect,
+        None => return html!({ sources.join(", ") }),
+    };
+
+    html!({
+        for sources.iter().map(|source| {
+            let onclick = onselect.reform({
+                let selection = (source.clone(), purl.to_string());
+                move |_| selection.clone()
+            });
+            html!(<Button variant={ButtonVariant::Link} label={source.clone()} {onclick} />)
+        })
+    })
+}
+
+#[derive(Clone)]
 struct PackageRef {
     label: String,
     purl: PackageUrl<'static>,
     pkg: data::PackageRef,
+    sources: Option<Vec<String>>,
+    originals: Option<Vec<String>>,
+    suggestion: Option<Option<data::PackageRef>>,
+    onselect: Option<Callback<(String, String)>>,
+    highlight: Option<String>,
 }
 
 impl PartialEq for PackageRef {
@@ -42,7 +94,51 @@ impl Ord for PackageRef {
             .cmp(&b.ty())
             .then_with(|| a.namespace().cmp(&b.namespace()))
             .then_with(|| a.name().cmp(b.name()))
-            .then_with(|| a.version().cmp(&b.version()))
+            .then_with(|| match (a.version(), b.version()) {
+                (Some(va), Some(vb)) => version::compare(a.ty(), va, vb),
+                (va, vb) => va.cmp(&vb),
+            })
+            .then_with(|| self.pkg.purl.cmp(&other.pkg.purl))
+    }
+}
+
+impl PackageRef {
+    /// Render the optional columns, the ones which are present.
+    fn extra_columns(&self) -> Vec<Html> {
+        let mut result = vec![];
+        if let Some(sources) = &self.sources {
+            result.push(render_sources(&self.pkg.purl, sources, &self.onselect));
+        }
+        if let Some(originals) = &self.originals {
+            result.push(html!(
+                { for originals.iter().map(|original| html!(
+                    <div>
+                        <code>{ original }</code>
+                        if original != &self.pkg.purl {
+                            <div><small>{ "Matched as " } <code>{ &self.pkg.purl }</code></small></div>
+                        }
+                    </div>
+                )) }
+            ));
+        }
+        if let Some(suggestion) = &self.suggestion {
+            result.push(match suggestion {
+                Some(pkg) => {
+                    let version = PackageUrl::from_str(&pkg.purl)
+                        .ok()
+                        .and_then(|purl| purl.version().map(ToString::to_string))
+                        .unwrap_or_else(|| pkg.purl.clone());
+                    html!(
+                        <>
+                            <Link<AppRoute> target={AppRoute::Package {package: pkg.purl.clone()}}>{ version }</Link<AppRoute>>
+                            {" "} <Trusted />
+                        </>
+                    )
+                }
+                None => html!(),
+            });
+        }
+        result
     }
 }
 
@@ -51,7 +147,7 @@ impl TableEntryRenderer for PackageRef {
         match context.column {
             0 => html!(
                 <>
-                    <Link<AppRoute> target={AppRoute::Package {package: self.pkg.purl.clone()}}>{&self.label}</Link<AppRoute>>
+                    <Link<AppRoute> target={AppRoute::Package {package: self.pkg.purl.clone()}}>{ highlight(&self.label, self.highlight.as_deref().unwrap_or_default()) }</Link<AppRoute>>
                     if let Some(true) = &self.pkg.trusted {
                         {" "} <Trusted />
                     }
@@ -66,16 +162,71 @@ impl TableEntryRenderer for PackageRef {
                     </>))
                 }
             ),
-            _ => html!(),
+            n => self.extra_columns().into_iter().nth(n - 4).unwrap_or_default(),
         }
             .into()
     }
 }
 
-#[function_component(PackageReferences)]
-pub fn package_refs(props: &PackageRefsProperties) -> Html {
-    let mut refs = Vec::with_capacity(props.refs.len());
-    for pkg in &props.refs {
+/// The number of references shown on a page.
+const PAGE_SIZE: usize = 100;
+
+/// The columns the references can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SortBy {
+    Name,
+    Version,
+    Type,
+}
+
+impl SortBy {
+    /// The sorting of a column, by its index in the table header.
+    fn from_index(index: usize) -> Option<Self> {
+        match index {
+            0 => Some(Self::Name),
+            1 => Some(Self::Version),
+            2 => Some(Self::Type),
+            _ => None,
+        }
+    }
+
+    fn compare(&self, a: &PackageRef, b: &PackageRef) -> Ordering {
+        match self {
+            Self::Name => a.label.cmp(&b.label).then_with(|| a.cmp(b)),
+            // versions are only comparable within the same type, keeping the order total
+            Self::Version => a
+                .purl
+                .ty()
+                .cmp(b.purl.ty())
+                .then_with(|| match (a.purl.version(), b.purl.version()) {
+                    (Some(va), Some(vb)) => version::compare(a.purl.ty(), va, vb),
+                    (va, vb) => va.cmp(&vb),
+                })
+                .then_with(|| a.cmp(b)),
+            Self::Type => a.cmp(b),
+        }
+    }
+}
+
+impl PackageRef {
+    /// If the (lowercase) filter matches the name or the package URL.
+    fn matches(&self, filter: &str) -> bool {
+        self.label.to_ascii_lowercase().contains(filter)
+            || self.pkg.purl.to_ascii_lowercase().contains(filter)
+    }
+}
+
+/// The data a table is built from, without the callbacks, which change on every render.
+type RowsData = (
+    Vec<data::PackageRef>,
+    Option<Rc<BTreeMap<String, Vec<String>>>>,
+    Option<Rc<BTreeMap<String, Vec<String>>>>,
+    Option<Rc<BTreeMap<String, data::PackageRef>>>,
+);
+
+fn into_rows((refs, sources, originals, suggestions): &RowsData) -> Vec<PackageRef> {
+    let mut rows = Vec::with_capacity(refs.len());
+    for pkg in refs {
         let purl = match PackageUrl::from_str(&pkg.purl) {
             Ok(purl) => purl,
             Err(_) => continue,
@@ -84,30 +235,196 @@ pub fn package_refs(props: &PackageRefsProperties) -> Html {
             Some(namespace) => format!("{namespace} : {name}", name = purl.name()),
             None => purl.name().to_string(),
         };
-        refs.push(PackageRef {
+        let normalized = normalize(&pkg.purl);
+        let sources = sources
+            .as_ref()
+            .map(|sources| sources.get(&normalized).cloned().unwrap_or_default());
+        let originals = originals
+            .as_ref()
+            .map(|originals| originals.get(&normalized).cloned().unwrap_or_default());
+        let suggestion = suggestions
+            .as_ref()
+            .map(|suggestions| suggestions.get(&pkg.purl).cloned());
+        rows.push(PackageRef {
             label,
             purl,
             pkg: pkg.clone(),
+            sources,
+            originals,
+            suggestion,
+            onselect: None,
+            highlight: None,
+        });
+    }
+    rows
+}
+
+/// A table of package references, which can be sorted, filtered and is paginated.
+#[function_component(PackageReferences)]
+pub fn package_refs(props: &PackageRefsProperties) -> Html {
+    let rows = use_memo(
+        into_rows,
+        (
+            props.refs.clone(),
+            props.sources.clone(),
+            props.originals.clone(),
+            props.suggestions.clone(),
+        ),
+    );
+
+    let filter = use_state_eq(String::new);
+    let only_trusted = use_state_eq(|| false);
+    // sorted by type initially, which also groups the packages of an ecosystem
+    let sortby = use_state_eq(|| TableHeaderSortBy {
+        index: 2,
+        asc: true,
+    });
+    let page = use_state_eq(|| 0usize);
+
+    let query = filter.to_ascii_lowercase();
+    let mut refs = rows
+        .iter()
+        .filter(|r| !*only_trusted || r.pkg.trusted.unwrap_or_default())
+        .filter(|r| r.matches(&query))
+        .collect::<Vec<_>>();
+
+    if let Some(sort) = SortBy::from_index(sortby.index) {
+        refs.sort_unstable_by(|a, b| {
+            let result = sort.compare(a, b);
+            match sortby.asc {
+                true => result,
+                false => result.reverse(),
+            }
         });
     }
 
-    refs.sort_unstable();
+    let total = refs.len();
+    let pages = (total + PAGE_SIZE - 1) / PAGE_SIZE;
+    // the page might be gone, after the references changed
+    let current = (*page).min(pages.saturating_sub(1));
+    let start = current * PAGE_SIZE;
+
+    // the filter is highlighted, or else the text the references were searched by
+    let highlight = match query.is_empty() {
+        true => props.highlight.as_ref().map(|h| h.to_ascii_lowercase()),
+        false => Some(query),
+    };
+    let entries = refs
+        .into_iter()
+        .skip(start)
+        .take(PAGE_SIZE)
+        .map(|r| PackageRef {
+            onselect: props.onselect.clone(),
+            highlight: highlight.clone(),
+            ..r.clone()
+        })
+        .collect::<Vec<_>>();
+
+    let oninput = {
+        let filter = filter.clone();
+        let page = page.clone();
+        Callback::from(move |data: String| {
+            filter.set(data);
+            page.set(0);
+        })
+    };
+    let onclear = {
+        let filter = filter.clone();
+        let page = page.clone();
+        Callback::from(move |_| {
+            filter.set(String::new());
+            page.set(0);
+        })
+    };
+    let on_change = {
+        let only_trusted = only_trusted.clone();
+        let page = page.clone();
+        Callback::from(move |checked: bool| {
+            only_trusted.set(checked);
+            page.set(0);
+        })
+    };
+    let onsort = {
+        let sortby = sortby.clone();
+        let page = page.clone();
+        Callback::from(move |value: TableHeaderSortBy| {
+            sortby.set(value);
+            page.set(0);
+        })
+    };
+    let onprevious = {
+        let page = page.clone();
+        Callback::from(move |_| page.set(current.saturating_sub(1)))
+    };
+    let onnext = {
+        let page = page.clone();
+        Callback::from(move |_| page.set(current + 1))
+    };
+
+    let mut columns = vec![
+        html_nested!(<TableColumn label="Name" sortby={*sortby} onsort={onsort.clone()} />),
+        html_nested!(<TableColumn label="Version" sortby={*sortby} onsort={onsort.clone()} />),
+        html_nested!(<TableColumn label="Type" sortby={*sortby} onsort={onsort} />),
+        html_nested!(<TableColumn/>),
+    ];
+    if props.sources.is_some() {
+        columns.push(html_nested!(<TableColumn label="Source file"/>));
+    }
+    if props.originals.is_some() {
+        columns.push(html_nested!(<TableColumn label="Package URL in SBOM"/>));
+    }
+    if props.suggestions.is_some() {
+        columns.push(html_nested!(<TableColumn label="Suggested replacement"/>));
+    }
 
     let header = html_nested!(
         <TableHeader>
-            <TableColumn label="Name" />
-            <TableColumn label="Version"/>
-            <TableColumn/>
-            <TableColumn/>
+            { for columns }
         </TableHeader>
     );
 
-    let entries = SharedTableModel::new(refs);
+    let entries = SharedTableModel::new(entries);
 
     html!(
-        <Table<SharedTableModel<PackageRef>>
-            mode={TableMode::CompactNoBorders}
-            {header} {entries}
-        />
+        <>
+            <Toolbar>
+                <ToolbarContent>
+                    <ToolbarItem r#type={ToolbarItemType::SearchFilter} width={["400px".to_string()]}>
+                        <TextInputGroup>
+                            <TextInputGroupMain
+                                icon={Icon::Search}
+                                placeholder="Filter"
+                                value={(*filter).clone()}
+                                {oninput}
+                            />
+                            if !filter.is_empty() {
+                                <TextInputGroupUtilities>
+                                    <Button icon={Icon::Times} variant={ButtonVariant::Plain} onclick={onclear} />
+                                </TextInputGroupUtilities>
+                            }
+                        </TextInputGroup>
+                    </ToolbarItem>
+                    <ToolbarItem>
+                        <Switch checked={*only_trusted} label="Only trusted" {on_change} />
+                    </ToolbarItem>
+                    if pages > 1 {
+                        <ToolbarItem>
+                            { format!("{} - {} of {total}", start + 1, (start + PAGE_SIZE).min(total)) }
+                        </ToolbarItem>
+                        <ToolbarItem>
+                            <Button variant={ButtonVariant::Secondary} label="Previous" onclick={onprevious} disabled={current == 0} />
+                        </ToolbarItem>
+                        <ToolbarItem>
+                            <Button variant={ButtonVariant::Secondary} label="Next" onclick={onnext} disabled={current + 1 >= pages} />
+                        </ToolbarItem>
+                    }
+                </ToolbarContent>
+            </Toolbar>
+
+            <Table<SharedTableModel<PackageRef>>
+                mode={TableMode::CompactNoBorders}
+                {header} {entries}
+            />
+        </>
     )
 }
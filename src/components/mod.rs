@@ -18,6 +18,18 @@ pub fn trusted() -> Html {
     html!(<Label color={Color::Gold} label="Trusted"/>)
 }
 
+/// Highlight the (lowercase) query in the text.
+pub fn highlight(text: &str, query: &str) -> Html {
+    // ASCII lowercase keeps the byte positions intact
+    match (query.is_empty(), text.to_ascii_lowercase().find(query)) {
+        (false, Some(start)) => {
+            let end = start + query.len();
+            html!(<>{ &text[..start] }<mark>{ &text[start..end] }</mark>{ &text[end..] }</>)
+        }
+        _ => html!({ text }),
+    }
+}
+
 pub fn remote_content<T, E, FB>(fetch: &UseAsyncState<T, E>, body: FB) -> Html
 where
     FB: FnOnce(&T) -> Html,
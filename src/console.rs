@@ -83,5 +83,6 @@ fn render(route: AppRoute) -> Html {
         AppRoute::Package { package } => html!(<pages::Package {package}/>),
         AppRoute::Vulnerability { cve } => html!(<pages::Vulnerability {cve}/>),
         AppRoute::SBOM => html!(<pages::SBOM/>),
+        AppRoute::SharedSBOM { id } => html!(<pages::SharedSBOM {id}/>),
     }
 }
@@ -7,7 +7,9 @@ mod components;
 mod console;
 mod hooks;
 mod pages;
+mod purl;
 mod utils;
+mod version;
 
 use wasm_bindgen::prelude::*;
 
@@ -26,4 +26,8 @@ pub enum AppRoute {
         cve: String,
     },
     SBOM,
+    /// A shared SBOM inspection
+    SharedSBOM {
+        id: String,
+    },
 }
@@ -186,7 +186,7 @@ fn package_information(props: &PackageInformationProperties) -> Html {
                     { remote_card(&fetch_versions, |data|
                         remote_card_title_badge("Versions", data.map(|r|r.len())),
                     |data| html!(
-                        <PackageVersions versions={data.0.clone()}/>
+                        <PackageVersions versions={data.0.clone()} current={props.purl.to_string()}/>
                     )) }
 
                 </Gallery>
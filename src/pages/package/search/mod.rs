@@ -3,6 +3,7 @@ mod lookup;
 use crate::backend::PackageService;
 use crate::components::deps::PackageReferences;
 use crate::hooks::use_backend;
+use itertools::Itertools;
 use packageurl::PackageUrl;
 use patternfly_yew::{
     next::{Toolbar, ToolbarContent},
@@ -19,14 +20,82 @@ fn default_purl() -> PackageUrl<'static> {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
 pub enum Criteria {
+    Text,
     Type,
     Namespace,
     Name,
     Version,
+    Qualifier,
+    Subpath,
+}
+
+/// Re-create the package URL, keeping only the qualifiers accepted by the filter, and using the
+/// subpath.
+fn rebuild<F>(
+    purl: &PackageUrl<'static>,
+    keep: F,
+    subpath: Option<&str>,
+) -> Result<PackageUrl<'static>, String>
+where
+    F: Fn(&str) -> bool,
+{
+    let mut result = PackageUrl::new(purl.ty().to_string(), purl.name().to_string())
+        .map_err(|err| err.to_string())?;
+    if let Some(namespace) = purl.namespace() {
+        result.with_namespace(namespace.to_string());
+    }
+    if let Some(version) = purl.version() {
+        result.with_version(version.to_string());
+    }
+    for (key, value) in purl.qualifiers().iter().filter(|(key, _)| keep(key)) {
+        result
+            .add_qualifier(key.to_string(), value.to_string())
+            .map_err(|err| err.to_string())?;
+    }
+    if let Some(subpath) = subpath {
+        result
+            .with_subpath(subpath.to_string())
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(result)
+}
+
+/// Set the rebuilt package URL, or the error if it couldn't be rebuilt.
+fn set_rebuilt(
+    state: &UseStateHandle<PackageUrl<'static>>,
+    error: &UseStateHandle<Option<String>>,
+    purl: Result<PackageUrl<'static>, String>,
+) {
+    match purl {
+        Ok(purl) => {
+            state.set(purl);
+            error.set(None);
+        }
+        Err(err) => error.set(Some(err)),
+    }
+}
+
+/// Remove the qualifier from the package URL.
+fn remove_qualifier(
+    state: UseStateHandle<PackageUrl<'static>>,
+    error: UseStateHandle<Option<String>>,
+    key: String,
+) -> Callback<()> {
+    Callback::from(move |_| {
+        set_rebuilt(
+            &state,
+            &error,
+            rebuild(&state, |k| k != key, state.subpath()),
+        );
+    })
 }
 
 impl Criteria {
-    fn onremove(&self, state: UseStateHandle<PackageUrl<'static>>) -> Option<Callback<()>> {
+    fn onremove(
+        &self,
+        state: UseStateHandle<PackageUrl<'static>>,
+        error: UseStateHandle<Option<String>>,
+    ) -> Option<Callback<()>> {
         match self {
             Self::Namespace => Some(Callback::from(move |_| {
                 let mut purl = (*state).clone();
@@ -38,14 +107,23 @@ impl Criteria {
                 purl.without_version();
                 state.set(purl);
             })),
+            Self::Subpath => Some(Callback::from(move |_| {
+                set_rebuilt(&state, &error, rebuild(&state, |_| true, None));
+            })),
             _ => None,
         }
     }
 
+    /// If the criteria sets the query of a text search.
+    fn is_query(&self) -> bool {
+        matches!(self, Self::Text | Self::Name)
+    }
+
     /// get the text for the edit component
     fn get(&self, state: &UseStateHandle<PackageUrl<'static>>) -> String {
         let purl = &**state;
         match self {
+            Self::Text => String::new(),
             Self::Type => purl.ty().to_string(),
             Self::Namespace => purl
                 .namespace()
@@ -53,19 +131,38 @@ impl Criteria {
                 .unwrap_or_default(),
             Self::Name => purl.name().to_string(),
             Self::Version => purl.version().map(ToString::to_string).unwrap_or_default(),
+            // qualifiers are added one at a time
+            Self::Qualifier => String::new(),
+            Self::Subpath => purl.subpath().map(ToString::to_string).unwrap_or_default(),
         }
     }
 
-    fn set(&self, state: &mut UseStateHandle<PackageUrl<'static>>, text: &UseStateHandle<String>) {
+    /// Set the text, keeping the package URL unchanged if the text isn't valid.
+    fn set(
+        &self,
+        state: &mut UseStateHandle<PackageUrl<'static>>,
+        text: &UseStateHandle<String>,
+    ) -> Result<(), String> {
         let mut purl = (**state).clone();
 
-        fn copy_optional(source: &PackageUrl, target: &mut PackageUrl) {
+        fn copy_optional(source: &PackageUrl, target: &mut PackageUrl) -> Result<(), String> {
             if let Some(namespace) = source.namespace() {
                 target.with_namespace(namespace.to_string());
             }
             if let Some(version) = source.version() {
                 target.with_version(version.to_string());
             }
+            for (key, value) in source.qualifiers() {
+                target
+                    .add_qualifier(key.to_string(), value.to_string())
+                    .map_err(|err| err.to_string())?;
+            }
+            if let Some(subpath) = source.subpath() {
+                target
+                    .with_subpath(subpath.to_string())
+                    .map_err(|err| err.to_string())?;
+            }
+            Ok(())
         }
 
         match self {
@@ -73,10 +170,10 @@ impl Criteria {
                 let name = purl.name().to_string();
                 purl = match PackageUrl::new((**text).clone(), name) {
                     Ok(mut new_purl) => {
-                        copy_optional(&purl, &mut new_purl);
+                        copy_optional(&purl, &mut new_purl)?;
                         new_purl
                     }
-                    Err(_err) => (**state).clone(),
+                    Err(err) => return Err(err.to_string()),
                 };
             }
             Self::Namespace => {
@@ -86,18 +183,99 @@ impl Criteria {
                 let r#type = purl.ty().to_string();
                 purl = match PackageUrl::new(r#type, (**text).clone()) {
                     Ok(mut new_purl) => {
-                        copy_optional(&purl, &mut new_purl);
+                        copy_optional(&purl, &mut new_purl)?;
                         new_purl
                     }
-                    Err(_err) => (**state).clone(),
+                    Err(err) => return Err(err.to_string()),
                 };
             }
             Self::Version => {
                 purl.with_version((**text).clone());
             }
+            Self::Qualifier => {
+                // entered as "key=value"
+                let (key, value) = text
+                    .split_once('=')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+                    .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+                    .ok_or_else(|| "Qualifiers must be entered as key=value".to_string())?;
+                purl.add_qualifier(key.to_string(), value.to_string())
+                    .map_err(|err| err.to_string())?;
+            }
+            Self::Subpath => {
+                let subpath = Some(text.as_str()).filter(|subpath| !subpath.is_empty());
+                purl = rebuild(&purl, |_| true, subpath)?;
+            }
+            // the text doesn't change the package URL, it switches to a text search
+            Self::Text => {}
         };
 
         state.set(purl);
+        Ok(())
+    }
+}
+
+/// A free-text search, optionally refined by parts of the package URL.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct TextQuery {
+    query: String,
+    ty: Option<String>,
+    namespace: Option<String>,
+    version: Option<String>,
+    subpath: Option<String>,
+}
+
+impl TextQuery {
+    fn new(query: String) -> Self {
+        Self {
+            query,
+            ..Default::default()
+        }
+    }
+
+    /// The refinement set by the criteria, `None` for the ones setting the query.
+    fn refinement(&mut self, criteria: Criteria) -> Option<&mut Option<String>> {
+        match criteria {
+            Criteria::Type => Some(&mut self.ty),
+            Criteria::Namespace => Some(&mut self.namespace),
+            Criteria::Version => Some(&mut self.version),
+            Criteria::Subpath => Some(&mut self.subpath),
+            // qualifiers only refine searches by package URL
+            Criteria::Text | Criteria::Name | Criteria::Qualifier => None,
+        }
+    }
+
+    /// get the text for the edit component
+    fn get(&self, criteria: Criteria) -> String {
+        match criteria {
+            Criteria::Text | Criteria::Name => self.query.clone(),
+            Criteria::Type => self.ty.clone().unwrap_or_default(),
+            Criteria::Namespace => self.namespace.clone().unwrap_or_default(),
+            Criteria::Version => self.version.clone().unwrap_or_default(),
+            Criteria::Subpath => self.subpath.clone().unwrap_or_default(),
+            Criteria::Qualifier => String::new(),
+        }
+    }
+
+    fn set(&mut self, criteria: Criteria, text: String) {
+        match self.refinement(criteria) {
+            Some(refinement) => *refinement = Some(text).filter(|text| !text.is_empty()),
+            None if criteria.is_query() && !text.is_empty() => self.query = text,
+            None => {}
+        }
+    }
+
+    /// The refinements, as sent to the backend.
+    fn refinements(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("type", &self.ty),
+            ("namespace", &self.namespace),
+            ("version", &self.version),
+            ("subpath", &self.subpath),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_deref().map(|value| (key, value)))
+        .collect()
     }
 }
 
@@ -120,6 +298,12 @@ pub fn package_search() -> Html {
             .unwrap_or_else(default_purl)
     });
 
+    // a free-text search, replacing the search by package URL while present
+    let text_query = use_state_eq(|| None::<TextQuery>);
+
+    // the error of the last change to the package URL, if it wasn't valid
+    let error = use_state_eq(|| None::<String>);
+
     let purl = (*state).clone();
 
     let mut filters = vec![];
@@ -134,27 +318,92 @@ pub fn package_search() -> Html {
         ));
     };
 
-    add_filter("Type", purl.ty().to_string(), None);
-    if let Some(namespace) = purl.namespace() {
-        add_filter(
-            "Namespace",
-            namespace.to_string(),
-            Criteria::Namespace.onremove(state.clone()),
-        );
-    }
-    add_filter("Name", purl.name().to_string(), None);
-    if let Some(version) = purl.version() {
-        add_filter(
-            "Version",
-            version.to_string(),
-            Criteria::Version.onremove(state.clone()),
-        );
+    if let Some(query) = &*text_query {
+        let onremove = |criteria: Criteria| {
+            let text_query = text_query.clone();
+            Some(Callback::from(move |_| {
+                let mut query = (*text_query).clone();
+                if let Some(refinement) = query.as_mut().and_then(|q| q.refinement(criteria)) {
+                    *refinement = None;
+                }
+                text_query.set(query);
+            }))
+        };
+
+        add_filter("Text", query.query.clone(), {
+            let text_query = text_query.clone();
+            Some(Callback::from(move |_| text_query.set(None)))
+        });
+        if let Some(ty) = &query.ty {
+            add_filter("Type", ty.clone(), onremove(Criteria::Type));
+        }
+        if let Some(namespace) = &query.namespace {
+            add_filter(
+                "Namespace",
+                namespace.clone(),
+                onremove(Criteria::Namespace),
+            );
+        }
+        if let Some(version) = &query.version {
+            add_filter("Version", version.clone(), onremove(Criteria::Version));
+        }
+        if let Some(subpath) = &query.subpath {
+            add_filter("Subpath", subpath.clone(), onremove(Criteria::Subpath));
+        }
+    } else {
+        add_filter("Type", purl.ty().to_string(), None);
+        if let Some(namespace) = purl.namespace() {
+            add_filter(
+                "Namespace",
+                namespace.to_string(),
+                Criteria::Namespace.onremove(state.clone(), error.clone()),
+            );
+        }
+        add_filter("Name", purl.name().to_string(), None);
+        if let Some(version) = purl.version() {
+            add_filter(
+                "Version",
+                version.to_string(),
+                Criteria::Version.onremove(state.clone(), error.clone()),
+            );
+        }
+        if let Some(subpath) = purl.subpath() {
+            add_filter(
+                "Subpath",
+                subpath.to_string(),
+                Criteria::Subpath.onremove(state.clone(), error.clone()),
+            );
+        }
+
+        // one chip per qualifier key
+        let qualifiers = purl
+            .qualifiers()
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .sorted()
+            .collect::<Vec<_>>();
+        if !qualifiers.is_empty() {
+            filters.push(html_nested!(
+                <ToolbarItem>
+                    <ChipGroup label="Qualifiers">
+                        { for qualifiers.into_iter().map(|(key, value)| {
+                            let onclose = remove_qualifier(state.clone(), error.clone(), key.clone());
+                            html_nested!(<Chip text={format!("{key}={value}")} {onclose}/>)
+                        }) }
+                    </ChipGroup>
+                </ToolbarItem>
+            ));
+        }
     }
 
     let onreset = {
         let state = state.clone();
+        let text_query = text_query.clone();
+        let error = error.clone();
         Callback::from(move |_| {
             state.set(default_purl());
+            text_query.set(None);
+            error.set(None);
         })
     };
 
@@ -169,36 +418,94 @@ pub fn package_search() -> Html {
 
     let onvariant = {
         let state = state.clone();
+        let text_query = text_query.clone();
         let text = text.clone();
         let criteria = criteria.clone();
+        let error = error.clone();
         Callback::from(move |data: Criteria| {
             criteria.set(Some(data));
-            text.set(data.get(&state));
+            error.set(None);
+            text.set(match &*text_query {
+                Some(query) => query.get(data),
+                None => data.get(&state),
+            });
         })
     };
 
-    let onclear = criteria.and_then(|c| {
-        c.onremove(state.clone())
-            .map(|cb| cb.reform(|_: MouseEvent| ()))
-    });
+    let onclear = match &*text_query {
+        Some(_) => criteria.map(|c| {
+            let text_query = text_query.clone();
+            Callback::from(move |_: MouseEvent| {
+                let mut query = (*text_query).clone();
+                match query.as_mut().and_then(|q| q.refinement(c)) {
+                    Some(refinement) => *refinement = None,
+                    // clearing the query ends the text search
+                    None if c.is_query() => query = None,
+                    None => {}
+                }
+                text_query.set(query);
+            })
+        }),
+        None => criteria.and_then(|c| {
+            c.onremove(state.clone(), error.clone())
+                .map(|cb| cb.reform(|_: MouseEvent| ()))
+        }),
+    };
     let onset = {
         let criteria = criteria.clone();
         let state = state.clone();
+        let text_query = text_query.clone();
         let text = text.clone();
-        Callback::from(move |_| {
-            if let Some(criteria) = *criteria {
-                criteria.set(&mut state.clone(), &text);
+        let error = error.clone();
+        Callback::from(move |_| match (*criteria, &*text_query) {
+            (Some(criteria), Some(query)) => {
+                let mut query = query.clone();
+                query.set(criteria, (*text).clone());
+                text_query.set(Some(query));
+            }
+            (Some(Criteria::Text), None) => {
+                if !text.is_empty() {
+                    text_query.set(Some(TextQuery::new((*text).clone())));
+                }
             }
+            (Some(criteria), None) => error.set(criteria.set(&mut state.clone(), &text).err()),
+            (None, _) => {}
         })
     };
 
-    let set_disabled = criteria.is_none();
+    let oninput = {
+        let text = text.clone();
+        let error = error.clone();
+        Callback::from(move |data| {
+            text.set(data);
+            error.set(None);
+        })
+    };
+
+    // qualifiers only refine searches by package URL, and not all backends can search by text
+    let qualifier_disabled = text_query.is_some();
+    let text_disabled = !backend.search.text;
+    let set_disabled = match *criteria {
+        None => true,
+        Some(Criteria::Qualifier) => qualifier_disabled,
+        Some(Criteria::Text) => text_disabled,
+        Some(_) => false,
+    };
 
     let search = {
         let service = service.clone();
         use_async_with_cloned_deps(
-            move |purl| async move { service.search(vec![purl]).await },
-            (*state).clone(),
+            move |(purl, text_query)| async move {
+                match text_query {
+                    Some(query) => {
+                        service
+                            .search_text(&query.query, &query.refinements())
+                            .await
+                    }
+                    None => service.search(vec![purl]).await,
+                }
+            },
+            ((*state).clone(), (*text_query).clone()),
         )
     };
 
@@ -214,8 +521,10 @@ pub fn package_search() -> Html {
     let backdrop = use_backdrop();
     let onfrompurl = {
         let state = state.clone();
+        let text_query = text_query.clone();
         let onclose = Callback::from(move |purl| {
             state.set(purl);
+            text_query.set(None);
         });
         Callback::from(move |_| {
             if let Some(backdrop) = &backdrop {
@@ -237,17 +546,28 @@ pub fn package_search() -> Html {
                             <InputGroup>
                                 <div style="width: 300px;">
                                     <Select<Criteria> icon={Icon::Filter} placeholder="Criteria" variant={SelectVariant::Single(onvariant)}>
-                                        {for Criteria::iter().map(|value| html_nested! (
-                                            <SelectOption<Criteria> {value}/>
-                                        )) }
+                                        {for Criteria::iter()
+                                            .filter(|value| match value {
+                                                Criteria::Qualifier => !qualifier_disabled,
+                                                Criteria::Text => !text_disabled,
+                                                _ => true,
+                                            })
+                                            .map(|value| html_nested! (
+                                                <SelectOption<Criteria> {value}/>
+                                            ))
+                                        }
                                     </Select<Criteria>>
                                 </div>
                                 <TextInputGroup>
                                     <TextInputGroupMain
                                         icon={Icon::Search}
-                                        placeholder="Filter"
+                                        placeholder={match *criteria {
+                                            Some(Criteria::Qualifier) if qualifier_disabled => "Not available for text searches",
+                                            Some(Criteria::Qualifier) => "key=value",
+                                            _ => "Filter",
+                                        }}
                                         value={(*text).clone()}
-                                        oninput={ Callback::from(move |data| text.set(data)) }
+                                        {oninput}
                                     />
                                     if let Some(onclear) = onclear {
                                         <TextInputGroupUtilities>
@@ -266,6 +586,10 @@ pub fn package_search() -> Html {
                 <ToolbarContent> { for filters.into_iter() } </ToolbarContent>
             </Toolbar>
 
+            if let Some(error) = &*error {
+                <Alert r#type={AlertType::Danger} inline=true title={error.clone()} />
+            }
+
             {
                 match &*search {
                     UseAsyncState::Pending | UseAsyncState::Processing => { html!(<Spinner/>) }
@@ -283,7 +607,8 @@ pub fn package_search() -> Html {
                     },
                     UseAsyncState::Ready(Ok(result)) => {
                         let refs = result.0.clone();
-                        html!(<PackageReferences {refs} />)
+                        let highlight = text_query.as_ref().map(|query| query.query.clone());
+                        html!(<PackageReferences {refs} {highlight} />)
                     },
                     UseAsyncState::Ready(Err(err)) => html!(
                         <Bullseye>
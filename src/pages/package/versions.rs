@@ -1,22 +1,99 @@
-use crate::{backend::data::PackageRef, components::Trusted, pages::AppRoute};
+//! The versions of a package, grouped by their major/minor line.
+
+use crate::{
+    backend::data::PackageRef, components::Trusted, pages::AppRoute, purl::normalize, version,
+};
 use packageurl::PackageUrl;
 use patternfly_yew::prelude::*;
+use std::collections::HashSet;
 use std::str::FromStr;
 use yew::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, Eq, Properties)]
 pub struct PackageVersionsProperties {
     pub versions: Vec<PackageRef>,
+    /// The package URL of the currently viewed version
+    pub current: String,
+}
+
+struct PackageVersion<'a> {
+    version: String,
+    purl: PackageUrl<'a>,
+    pkg: &'a PackageRef,
+    /// If this is the newest version of its line
+    latest: bool,
+    /// If this is the newest trusted version of its line
+    latest_trusted: bool,
+}
+
+impl PackageVersion<'_> {
+    fn trusted(&self) -> bool {
+        self.pkg.trusted.unwrap_or_default()
+    }
+}
+
+/// The versions of a major/minor line, newest first.
+struct Line<'a> {
+    name: String,
+    versions: Vec<PackageVersion<'a>>,
+}
+
+/// The major/minor line of a version, like `1.2.x`, or `None` if it doesn't start with a number.
+fn line(version: &str) -> Option<String> {
+    // skip the prefix and the epoch (of PEP 440 and RPM versions)
+    let version = version.trim_start_matches('v');
+    let version = match version.split_once(['!', ':']) {
+        Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => version,
+    };
+
+    let numbers = version
+        .split(|c: char| !c.is_ascii_digit())
+        .take_while(|number| !number.is_empty())
+        .take(2)
+        .collect::<Vec<_>>();
+
+    match numbers.as_slice() {
+        [] => None,
+        [major] => Some(format!("{major}.x")),
+        [major, minor, ..] => Some(format!("{major}.{minor}.x")),
+    }
+}
+
+/// Group the versions by their line, keeping the order of the versions, and mark the newest
+/// (trusted) version of each line.
+fn group(versions: Vec<PackageVersion<'_>>) -> Vec<Line<'_>> {
+    let mut result: Vec<Line> = vec![];
+    for v in versions {
+        let name = line(&v.version).unwrap_or_else(|| "Other".to_string());
+        match result.iter_mut().find(|line| line.name == name) {
+            Some(line) => line.versions.push(v),
+            None => result.push(Line {
+                name,
+                versions: vec![v],
+            }),
+        }
+    }
+
+    for line in &mut result {
+        if let Some(v) = line.versions.first_mut() {
+            v.latest = true;
+        }
+        if let Some(v) = line.versions.iter_mut().find(|v| v.trusted()) {
+            v.latest_trusted = true;
+        }
+    }
+
+    result
 }
 
 #[function_component(PackageVersions)]
 pub fn package_versions(props: &PackageVersionsProperties) -> Html {
-    #[derive(PartialEq)]
-    struct PackageVersion<'a> {
-        version: String,
-        purl: PackageUrl<'a>,
-        pkg: &'a PackageRef,
-    }
+    let only_trusted = use_state_eq(|| false);
+    // the lines the user toggled, compared to their initial state
+    let toggled = use_state_eq(HashSet::<String>::new);
+
+    let current = normalize(&props.current);
 
     let mut versions = Vec::with_capacity(props.versions.len());
     for pkg in &props.versions {
@@ -28,26 +105,110 @@ pub fn package_versions(props: &PackageVersionsProperties) -> Html {
             Some(version) => version.to_string(),
             None => continue,
         };
-        versions.push(PackageVersion { version, purl, pkg });
+        versions.push(PackageVersion {
+            version,
+            purl,
+            pkg,
+            latest: false,
+            latest_trusted: false,
+        });
     }
 
-    // FIXME: do numeric version sorting
-    versions.sort_unstable_by(|a, b| a.version.cmp(&b.version).reverse());
+    versions
+        .sort_unstable_by(|a, b| version::compare(a.purl.ty(), &a.version, &b.version).reverse());
+
+    // the latest versions are those of all versions, only the displayed ones are filtered
+    let mut lines = group(versions);
+    if *only_trusted {
+        for line in &mut lines {
+            line.versions.retain(|v| v.trusted());
+        }
+        lines.retain(|line| !line.versions.is_empty());
+    }
+
+    let on_change = {
+        let only_trusted = only_trusted.clone();
+        Callback::from(move |checked: bool| only_trusted.set(checked))
+    };
+
+    let ontoggle = {
+        let toggled = toggled.clone();
+        Callback::from(move |name: String| {
+            let mut t = (*toggled).clone();
+            if !t.remove(&name) {
+                t.insert(name);
+            }
+            toggled.set(t);
+        })
+    };
+
+    let render_version = |v: &PackageVersion| {
+        let is_current = normalize(&v.pkg.purl) == current;
+        html!(<>
+            <yew_nested_router::components::Link<AppRoute>
+                target={AppRoute::Package { package: v.purl.to_string() }}
+            >
+                if is_current {
+                    <strong>{ &v.version }</strong>
+                } else {
+                    { &v.version }
+                }
+            </yew_nested_router::components::Link<AppRoute>>
+            if v.trusted() {
+                {" "}<Trusted/>
+            }
+            if is_current {
+                {" "}<Label compact=true label="Current" color={Color::Blue}/>
+            }
+            if v.latest {
+                {" "}<Label compact=true label="Latest" color={Color::Green}/>
+            } else if v.latest_trusted {
+                {" "}<Label compact=true label="Latest trusted" color={Color::Green}/>
+            }
+        </>)
+    };
+
+    let render_line = |n: usize, line: &Line| {
+        // the newest line, and the line of the current version, are expanded initially
+        let contains_current = line
+            .versions
+            .iter()
+            .any(|v| normalize(&v.pkg.purl) == current);
+        let expanded = (n == 0 || contains_current) != toggled.contains(&line.name);
+
+        let onclick = ontoggle.reform({
+            let name = line.name.clone();
+            move |_| name.clone()
+        });
+        let icon = match expanded {
+            true => Icon::AngleDown,
+            false => Icon::AngleRight,
+        };
+
+        html!(
+            <>
+                <div>
+                    <Button variant={ButtonVariant::Plain} {icon} {onclick} />
+                    <strong>{ &line.name }</strong>
+                    { " " } <Badge read=true> { line.versions.len() } </Badge>
+                </div>
+                if expanded {
+                    <List r#type={ListType::Plain}>
+                        { for line.versions.iter().map(render_version) }
+                    </List>
+                }
+            </>
+        )
+    };
 
     html!(
-        <List r#type={ListType::Plain}>
-            {for versions.iter().map(|v|{
-                html!(<>
-                    <yew_nested_router::components::Link<AppRoute>
-                        target={AppRoute::Package { package: v.purl.to_string() }}
-                    >
-                        {&v.version}
-                    </yew_nested_router::components::Link<AppRoute>>
-                    if v.pkg.trusted.unwrap_or_default() {
-                        {" "}<Trusted/>
-                    }
-                </>)
-            })}
-        </List>
+        <>
+            <Switch checked={*only_trusted} label="Only trusted versions" {on_change} />
+            if lines.is_empty() {
+                <p>{ "No versions" }</p>
+            } else {
+                { for lines.iter().enumerate().map(|(n, line)| render_line(n, line)) }
+            }
+        </>
     )
 }
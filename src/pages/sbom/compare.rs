@@ -0,0 +1,383 @@
+//! Comparing two sets of SBOMs, e.g. of two releases.
+
+use super::{
+    model::{Component, SourceFile},
+    search::{search_content, use_chunked_lookup, use_chunked_search},
+    tree::Status,
+    CommonHeader,
+};
+use crate::{components::count_title, pages::AppRoute, purl::normalize, version};
+use packageurl::PackageUrl;
+use patternfly_yew::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+use std::str::FromStr;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+use yew_nested_router::components::Link;
+
+/// The state of a component in one of the compared releases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Release {
+    versions: Vec<String>,
+    /// The status according to the backend, `None` if the component doesn't have a package URL
+    status: Option<Status>,
+    vulnerabilities: BTreeSet<String>,
+}
+
+/// A component which differs between the two releases.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffEntry {
+    name: String,
+    old: Option<Release>,
+    new: Option<Release>,
+}
+
+impl DiffEntry {
+    fn vulnerabilities(&self) -> (Vec<&String>, Vec<&String>) {
+        let empty = BTreeSet::new();
+        let old = self
+            .old
+            .as_ref()
+            .map(|r| &r.vulnerabilities)
+            .unwrap_or(&empty);
+        let new = self
+            .new
+            .as_ref()
+            .map(|r| &r.vulnerabilities)
+            .unwrap_or(&empty);
+        (new.difference(old).collect(), old.difference(new).collect())
+    }
+}
+
+fn render_versions(release: &Option<Release>) -> Html {
+    match release {
+        Some(release) => html!({ release.versions.join(", ") }),
+        None => html!(),
+    }
+}
+
+fn render_status(status: Option<Status>) -> Html {
+    status.map(|status| status.label()).unwrap_or_default()
+}
+
+fn render_vulnerability(cve: &str, color: Color) -> Html {
+    html!(
+        <>
+            <Link<AppRoute> target={AppRoute::Vulnerability { cve: cve.to_string() }}>
+                <Label label={cve.to_string()} {color} compact=true />
+            </Link<AppRoute>>
+            {" "}
+        </>
+    )
+}
+
+impl TableEntryRenderer for DiffEntry {
+    fn render_cell(&self, context: &CellContext) -> Cell {
+        match context.column {
+            0 => html!({ &self.name }),
+            1 => render_versions(&self.old),
+            2 => render_versions(&self.new),
+            3 => match (&self.old, &self.new) {
+                (Some(old), Some(new)) if old.status != new.status => html!(
+                    <>{ render_status(old.status) } {" → "} { render_status(new.status) }</>
+                ),
+                (_, Some(release)) | (Some(release), None) => render_status(release.status),
+                (None, None) => html!(),
+            },
+            4 => {
+                let (introduced, resolved) = self.vulnerabilities();
+                html!(
+                    <>
+                        { for introduced.into_iter().map(|cve| render_vulnerability(cve, Color::Red)) }
+                        { for resolved.into_iter().map(|cve| render_vulnerability(cve, Color::Green)) }
+                    </>
+                )
+            }
+            _ => html!(),
+        }
+        .into()
+    }
+}
+
+/// The differences between two releases.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    added: Vec<DiffEntry>,
+    removed: Vec<DiffEntry>,
+    changed: Vec<DiffEntry>,
+}
+
+/// The identity of a component, independent of its version.
+fn identity(component: &Component) -> String {
+    component
+        .purl
+        .as_deref()
+        .and_then(|purl| PackageUrl::from_str(purl).ok())
+        .and_then(|purl| {
+            let mut id = PackageUrl::new(purl.ty().to_string(), purl.name().to_string()).ok()?;
+            if let Some(namespace) = purl.namespace() {
+                id.with_namespace(namespace.to_string());
+            }
+            Some(id.to_string())
+        })
+        .unwrap_or_else(|| component.full_name())
+}
+
+#[derive(Default)]
+struct Collected {
+    name: String,
+    /// The type of the package URL, selecting how versions are ordered.
+    ty: String,
+    versions: BTreeSet<String>,
+    purls: BTreeSet<String>,
+}
+
+/// Collect the components of a release, by their identity.
+fn collect(files: &[SourceFile]) -> BTreeMap<String, Collected> {
+    let mut result = BTreeMap::<String, Collected>::new();
+    for component in files.iter().flat_map(|file| file.sbom.components.iter()) {
+        let entry = result.entry(identity(component)).or_default();
+        entry.name = component.full_name();
+        if let Some(version) = &component.version {
+            entry.versions.insert(version.clone());
+        }
+        if let Some(purl) = &component.purl {
+            if let Ok(parsed) = PackageUrl::from_str(purl) {
+                entry.ty = parsed.ty().to_string();
+            }
+            entry.purls.insert(purl.clone());
+        }
+    }
+    result
+}
+
+/// Compare two releases, using the found packages to compare their status.
+pub fn into_diff(
+    old: &[SourceFile],
+    new: &[SourceFile],
+    refs: &[PackageRef],
+    packages: &[Package],
+) -> Diff {
+    let found = refs
+        .iter()
+        .map(|r| (normalize(&r.purl), r.trusted.unwrap_or_default()))
+        .collect::<HashMap<_, _>>();
+    let vulnerabilities = packages
+        .iter()
+        .filter_map(|p| {
+            p.purl.as_ref().map(|purl| {
+                (
+                    normalize(purl),
+                    p.vulnerabilities
+                        .iter()
+                        .map(|v| v.cve.clone())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        })
+        .collect::<HashMap<_, _>>();
+
+    let release = |collected: &Collected| {
+        let statuses = collected
+            .purls
+            .iter()
+            .map(|purl| Status::of(purl, &found))
+            .collect::<Vec<_>>();
+        // the best status of all package URLs
+        let status = [Status::Trusted, Status::Found, Status::Unknown]
+            .into_iter()
+            .find(|status| statuses.contains(status));
+
+        let mut versions = collected.versions.iter().cloned().collect::<Vec<_>>();
+        versions.sort_by(|a, b| version::compare(&collected.ty, a, b));
+
+        Release {
+            versions,
+            status,
+            vulnerabilities: collected
+                .purls
+                .iter()
+                .flat_map(|purl| vulnerabilities.get(&normalize(purl)).into_iter().flatten())
+                .cloned()
+                .collect(),
+        }
+    };
+
+    let old = collect(old);
+    let mut new = collect(new);
+
+    let mut diff = Diff::default();
+
+    for (id, old) in old {
+        match new.remove(&id) {
+            None => diff.removed.push(DiffEntry {
+                name: old.name.clone(),
+                old: Some(release(&old)),
+                new: None,
+            }),
+            Some(new) if new.versions != old.versions => diff.changed.push(DiffEntry {
+                name: new.name.clone(),
+                old: Some(release(&old)),
+                new: Some(release(&new)),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (_, new) in new {
+        diff.added.push(DiffEntry {
+            name: new.name.clone(),
+            old: None,
+            new: Some(release(&new)),
+        });
+    }
+
+    diff
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct DiffTableProperties {
+    entries: Vec<DiffEntry>,
+}
+
+#[function_component(DiffTable)]
+fn diff_table(props: &DiffTableProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="Component"/>
+        <TableColumn label="Old version"/>
+        <TableColumn label="New version"/>
+        <TableColumn label="Trust"/>
+        <TableColumn label="Vulnerabilities (new / resolved)"/>
+      </TableHeader>
+    );
+
+    let entries = use_memo(
+        |entries| SharedTableModel::new(entries.clone()),
+        props.entries.clone(),
+    );
+
+    html!(
+        if props.entries.is_empty() {
+            <Bullseye>
+                <EmptyState title="No changes" icon={Icon::CheckCircle} />
+            </Bullseye>
+        } else {
+            <Table<SharedTableModel<DiffEntry>>
+                {header} entries={(*entries).clone()}
+                mode={TableMode::Compact}
+            >
+            </Table<SharedTableModel<DiffEntry>>>
+        }
+    )
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct CompareProperties {
+    pub old: Rc<Vec<SourceFile>>,
+    pub new: Rc<Vec<SourceFile>>,
+}
+
+#[function_component(Compare)]
+pub fn compare(props: &CompareProperties) -> Html {
+    let tab = use_state_eq(|| 0);
+    let onselect = {
+        let tab = tab.clone();
+        Callback::from(move |index: usize| {
+            tab.set(index);
+        })
+    };
+
+    let purls = use_memo(
+        |(old, new)| {
+            old.iter()
+                .chain(new.iter())
+                .flat_map(|file| file.sbom.purls())
+                .map(normalize)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
+        },
+        (props.old.clone(), props.new.clone()),
+    );
+
+    let fetch = use_chunked_search(purls.clone());
+
+    // the full information of the found packages, once they are known
+    let found = use_memo(
+        |found| {
+            found.as_ref().map(|found| {
+                Rc::new(
+                    found
+                        .0
+                        .iter()
+                        .map(|pkg| pkg.purl.clone())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        },
+        fetch.data().cloned(),
+    );
+    let fetch_packages = use_chunked_lookup((*found).clone());
+
+    let diff = use_memo(
+        |(refs, packages, old, new)| match (refs, packages) {
+            (Some(refs), Some(packages)) => into_diff(old, new, refs, packages),
+            _ => Diff::default(),
+        },
+        (
+            fetch.data().cloned(),
+            fetch_packages.data().cloned(),
+            props.old.clone(),
+            props.new.clone(),
+        ),
+    );
+
+    let names = |files: &[SourceFile]| {
+        files
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    html!(
+        <>
+            <CommonHeader />
+
+            <PageSection variant={PageSectionVariant::Light}>
+                <Content>
+                    <p>
+                        { "Comparing " } <strong>{ names(&props.old) }</strong>
+                        { " with " } <strong>{ names(&props.new) }</strong>
+                    </p>
+                </Content>
+            </PageSection>
+
+            { search_content(&fetch, |_| search_content(&fetch_packages, |_| html!(
+                <>
+                    <PageSection r#type={PageSectionType::Tabs} variant={PageSectionVariant::Light} sticky={[PageSectionSticky::Top]}>
+                        <Tabs inset={TabInset::Page} detached=true onselect={onselect.clone()}>
+                            <Tab label={count_title(diff.added.len(), "Added", "Added")} />
+                            <Tab label={count_title(diff.removed.len(), "Removed", "Removed")} />
+                            <Tab label={count_title(diff.changed.len(), "Changed", "Changed")} />
+                        </Tabs>
+                    </PageSection>
+
+                    <PageSection hidden={*tab != 0} fill={PageSectionFill::Fill}>
+                        <DiffTable entries={diff.added.clone()} />
+                    </PageSection>
+
+                    <PageSection hidden={*tab != 1} fill={PageSectionFill::Fill}>
+                        <DiffTable entries={diff.removed.clone()} />
+                    </PageSection>
+
+                    <PageSection hidden={*tab != 2} fill={PageSectionFill::Fill}>
+                        <DiffTable entries={diff.changed.clone()} />
+                    </PageSection>
+                </>
+            ))) }
+        </>
+    )
+}
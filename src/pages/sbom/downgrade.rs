@@ -0,0 +1,263 @@
+//! Reading CycloneDX 1.5 documents as 1.4, which is the latest version cyclonedx-bom can read.
+//!
+//! Everything 1.5 added is removed before handing the document to the 1.4 parser. Content which
+//! can't be represented in 1.4, like the new component types, is rejected.
+
+use serde_json::Value;
+use std::ops::Range;
+
+/// The component types of CycloneDX 1.4, 1.5 added more.
+const COMPONENT_TYPES: &[&str] = &[
+    "application",
+    "framework",
+    "library",
+    "container",
+    "operating-system",
+    "device",
+    "firmware",
+    "file",
+];
+
+/// The external reference types of CycloneDX 1.4, 1.5 added more.
+const EXTERNAL_REFERENCE_TYPES: &[&str] = &[
+    "vcs",
+    "issue-tracker",
+    "website",
+    "advisories",
+    "bom",
+    "mailing-list",
+    "social",
+    "chat",
+    "documentation",
+    "support",
+    "distribution",
+    "license",
+    "build-meta",
+    "build-system",
+    "release-notes",
+    "other",
+];
+
+#[derive(Debug, thiserror::Error)]
+#[error("CycloneDX 1.5 content not supported: {0}")]
+pub struct UnsupportedContent(String);
+
+/// Remove the properties from a JSON object.
+fn remove(value: &mut Value, properties: &[&str]) {
+    if let Some(object) = value.as_object_mut() {
+        for property in properties {
+            object.remove(*property);
+        }
+    }
+}
+
+/// The items of a JSON array property, none if it's missing.
+fn items<'a>(value: &'a mut Value, property: &str) -> impl Iterator<Item = &'a mut Value> {
+    value
+        .get_mut(property)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+}
+
+/// 1.5 added the `bom-ref` to organizational entities and contacts.
+fn entity(value: &mut Value) {
+    remove(value, &["bom-ref"]);
+    for contact in items(value, "contact") {
+        remove(contact, &["bom-ref"]);
+    }
+}
+
+/// 1.5 replaced the list of tools with an object of components and services, which has no 1.4
+/// counterpart.
+fn tools(value: &mut Value) {
+    if value.get("tools").map(Value::is_object).unwrap_or_default() {
+        remove(value, &["tools"]);
+    }
+}
+
+fn licenses(value: &mut Value) {
+    for license in items(value, "licenses") {
+        remove(license, &["bom-ref"]);
+        if let Some(license) = license.get_mut("license") {
+            remove(license, &["bom-ref", "licensing", "properties"]);
+        }
+    }
+}
+
+fn external_references(value: &mut Value) {
+    if let Some(references) = value
+        .get_mut("externalReferences")
+        .and_then(Value::as_array_mut)
+    {
+        references.retain(|reference| {
+            reference
+                .get("type")
+                .and_then(Value::as_str)
+                .map(|ty| EXTERNAL_REFERENCE_TYPES.contains(&ty))
+                .unwrap_or_default()
+        });
+    }
+}
+
+fn component(value: &mut Value) -> Result<(), UnsupportedContent> {
+    if let Some(ty) = value.get("type").and_then(Value::as_str) {
+        if !COMPONENT_TYPES.contains(&ty) {
+            return Err(UnsupportedContent(format!("component type '{ty}'")));
+        }
+    }
+
+    remove(value, &["modelCard", "data"]);
+    if let Some(evidence) = value.get_mut("evidence") {
+        remove(evidence, &["identity", "occurrences", "callstack"]);
+        licenses(evidence);
+    }
+    if let Some(supplier) = value.get_mut("supplier") {
+        entity(supplier);
+    }
+    licenses(value);
+    external_references(value);
+
+    for child in items(value, "components") {
+        component(child)?;
+    }
+    Ok(())
+}
+
+fn service(value: &mut Value) {
+    remove(value, &["trustZone"]);
+    for data in items(value, "data") {
+        // 1.4 only knows the flow and the classification
+        if let Some(data) = data.as_object_mut() {
+            data.retain(|key, _| key == "flow" || key == "classification");
+        }
+    }
+    if let Some(provider) = value.get_mut("provider") {
+        entity(provider);
+    }
+    licenses(value);
+    external_references(value);
+
+    for child in items(value, "services") {
+        service(child);
+    }
+}
+
+fn vulnerability(value: &mut Value) {
+    remove(value, &["rejected", "proofOfConcept", "workaround"]);
+    if let Some(analysis) = value.get_mut("analysis") {
+        remove(analysis, &["firstIssued", "lastUpdated"]);
+    }
+    if let Some(credits) = value.get_mut("credits") {
+        for organization in items(credits, "organizations") {
+            entity(organization);
+        }
+        for individual in items(credits, "individuals") {
+            remove(individual, &["bom-ref"]);
+        }
+    }
+    tools(value);
+}
+
+/// Turn a CycloneDX 1.5 JSON document into a 1.4 one.
+pub fn json(bom: &mut Value) -> Result<(), UnsupportedContent> {
+    remove(bom, &["annotations", "formulation", "properties"]);
+    if let Some(bom) = bom.as_object_mut() {
+        bom.insert("specVersion".to_string(), "1.4".into());
+    }
+
+    if let Some(metadata) = bom.get_mut("metadata") {
+        remove(metadata, &["lifecycles"]);
+        tools(metadata);
+        for author in items(metadata, "authors") {
+            remove(author, &["bom-ref"]);
+        }
+        for property in ["manufacture", "supplier"] {
+            if let Some(value) = metadata.get_mut(property) {
+                entity(value);
+            }
+        }
+        licenses(metadata);
+        if let Some(value) = metadata.get_mut("component") {
+            component(value)?;
+        }
+    }
+
+    for value in items(bom, "components") {
+        component(value)?;
+    }
+    for value in items(bom, "services") {
+        service(value);
+    }
+    external_references(bom);
+    for composition in items(bom, "compositions") {
+        remove(composition, &["bom-ref", "vulnerabilities"]);
+    }
+    for value in items(bom, "vulnerabilities") {
+        vulnerability(value);
+    }
+
+    Ok(())
+}
+
+/// If the element added by 1.5 must be removed, by the name of its parent and its own name.
+fn removed_element(node: roxmltree::Node) -> bool {
+    let parent = match node.parent_element() {
+        Some(parent) => parent.tag_name().name(),
+        None => return false,
+    };
+    let has_child = |name: &str| node.children().any(|c| c.tag_name().name() == name);
+
+    match (parent, node.tag_name().name()) {
+        ("bom", "annotations" | "formulation" | "properties") => true,
+        ("metadata", "lifecycles") => true,
+        ("metadata" | "vulnerability", "tools") => has_child("components") || has_child("services"),
+        ("component", "modelCard" | "data") => true,
+        ("evidence", "identity" | "occurrences" | "callstack") => true,
+        ("license", "licensing" | "properties") => true,
+        ("service", "trustZone") => true,
+        ("data", "dataflow") => true,
+        ("composition", "vulnerabilities") => true,
+        ("vulnerability", "rejected" | "proofOfConcept" | "workaround") => true,
+        ("analysis", "firstIssued" | "lastUpdated") => true,
+        ("externalReferences", "reference") => !node
+            .attribute("type")
+            .map(|ty| EXTERNAL_REFERENCE_TYPES.contains(&ty))
+            .unwrap_or_default(),
+        _ => false,
+    }
+}
+
+/// Remove the elements 1.5 added from an XML document. The namespace still needs to be changed.
+pub fn xml(data: &str, doc: &roxmltree::Document) -> Result<String, UnsupportedContent> {
+    let mut removed = Vec::<Range<usize>>::new();
+    for node in doc.descendants().filter(|node| node.is_element()) {
+        if node.tag_name().name() == "component" {
+            if let Some(ty) = node.attribute("type") {
+                if !COMPONENT_TYPES.contains(&ty) {
+                    return Err(UnsupportedContent(format!("component type '{ty}'")));
+                }
+            }
+        }
+
+        // descendants come in document order, so a removed parent comes before its children
+        let range = node.range();
+        let inside_removed = removed
+            .last()
+            .map(|last| last.end >= range.end)
+            .unwrap_or_default();
+        if !inside_removed && removed_element(node) {
+            removed.push(range);
+        }
+    }
+
+    let mut result = String::with_capacity(data.len());
+    let mut start = 0;
+    for range in removed {
+        result.push_str(&data[start..range.start]);
+        start = range.end;
+    }
+    result.push_str(&data[start..]);
+
+    Ok(result)
+}
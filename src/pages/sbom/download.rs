@@ -0,0 +1,147 @@
+//! Downloading SBOMs from a URL.
+
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// The maximum size of a document we are willing to download.
+pub const MAX_DOWNLOAD_SIZE: usize = 50 * 1024 * 1024;
+
+/// Progress of a download.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub received: usize,
+    /// The expected size, if the server told us
+    pub total: Option<usize>,
+}
+
+impl std::fmt::Display for Progress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.total {
+            Some(total) if total > 0 => write!(
+                f,
+                "Downloading… {} of {} ({}%)",
+                human_size(self.received),
+                human_size(total),
+                self.received * 100 / total
+            ),
+            _ => write!(f, "Downloading… {}", human_size(self.received)),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DownloadError {
+    #[error("Failed to download '{url}'. The server is either not reachable or does not allow cross-origin requests (CORS): {message}")]
+    Request { url: String, message: String },
+    #[error("Failed to download '{url}': the server responded with {status} {status_text}")]
+    Status {
+        url: String,
+        status: u16,
+        status_text: String,
+    },
+    #[error(
+        "Failed to download '{url}': the document exceeds the size limit of {}",
+        human_size(MAX_DOWNLOAD_SIZE)
+    )]
+    TooLarge { url: String },
+    #[error("Failed to download '{url}': {message}")]
+    Read { url: String, message: String },
+    #[error("Failed to download '{url}': the document is not valid UTF-8")]
+    Encoding { url: String },
+}
+
+/// Download a document, reporting the progress while receiving the content.
+pub async fn download<F>(url: &str, progress: F) -> Result<String, DownloadError>
+where
+    F: Fn(Progress),
+{
+    let read_err = |err: JsValue| DownloadError::Read {
+        url: url.to_string(),
+        message: js_message(err),
+    };
+
+    let response = gloo_net::http::Request::get(url)
+        .send()
+        .await
+        .map_err(|err| DownloadError::Request {
+            url: url.to_string(),
+            message: err.to_string(),
+        })?;
+
+    if !response.ok() {
+        return Err(DownloadError::Status {
+            url: url.to_string(),
+            status: response.status(),
+            status_text: response.status_text(),
+        });
+    }
+
+    let total = response
+        .headers()
+        .get("content-length")
+        .and_then(|len| len.parse::<usize>().ok());
+
+    if matches!(total, Some(total) if total > MAX_DOWNLOAD_SIZE) {
+        return Err(DownloadError::TooLarge {
+            url: url.to_string(),
+        });
+    }
+
+    let mut data = Vec::with_capacity(total.unwrap_or_default());
+    progress(Progress { received: 0, total });
+
+    if let Some(body) = response.body() {
+        let reader: web_sys::ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+        loop {
+            let chunk = JsFuture::from(reader.read()).await.map_err(read_err)?;
+
+            let done = js_sys::Reflect::get(&chunk, &"done".into())
+                .map_err(read_err)?
+                .as_bool()
+                .unwrap_or(true);
+            if done {
+                break;
+            }
+
+            let value = js_sys::Reflect::get(&chunk, &"value".into()).map_err(read_err)?;
+            data.extend(js_sys::Uint8Array::new(&value).to_vec());
+
+            if data.len() > MAX_DOWNLOAD_SIZE {
+                // the server might not have sent a content length, or lied about it
+                let _ = reader.cancel();
+                return Err(DownloadError::TooLarge {
+                    url: url.to_string(),
+                });
+            }
+
+            progress(Progress {
+                received: data.len(),
+                total,
+            });
+        }
+    }
+
+    String::from_utf8(data).map_err(|_| DownloadError::Encoding {
+        url: url.to_string(),
+    })
+}
+
+fn js_message(err: JsValue) -> String {
+    err.as_string().unwrap_or_else(|| format!("{err:?}"))
+}
+
+fn human_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} {}", UNITS[0]),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
@@ -0,0 +1,255 @@
+//! Enriching CycloneDX SBOMs with the information of the backend.
+
+use super::{
+    export::save,
+    model::{Source, SourceFile},
+    parse::Format,
+    tree::Status,
+};
+use crate::purl::normalize;
+use cyclonedx_bom::{
+    external_models::normalized_string::NormalizedString,
+    models::{
+        component::Component,
+        property::{Properties, Property},
+        vulnerability::{Vulnerabilities, Vulnerability},
+        vulnerability_target::{VulnerabilityTarget, VulnerabilityTargets},
+    },
+    prelude::Bom,
+};
+use patternfly_yew::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::rc::Rc;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+
+/// The name of the property carrying the status of a component.
+const STATUS_PROPERTY: &str = "trust:status";
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnrichError {
+    #[error("Failed to write JSON: {0}")]
+    Json(#[from] cyclonedx_bom::errors::JsonWriteError),
+    #[error("Failed to write XML: {0}")]
+    Xml(#[from] cyclonedx_bom::errors::XmlWriteError),
+    #[error("Output is not valid UTF-8")]
+    Encoding(#[from] std::string::FromUtf8Error),
+}
+
+fn status_property(status: Status) -> &'static str {
+    match status {
+        Status::Trusted => "trusted",
+        Status::Found => "found",
+        Status::Unknown => "unknown",
+    }
+}
+
+struct Enricher<'a> {
+    found: &'a HashMap<String, bool>,
+    /// The CVEs affecting a (normalized) package URL
+    vulnerabilities: &'a HashMap<String, Vec<String>>,
+    bom_refs: HashSet<String>,
+    /// The references of the components affected by a CVE
+    affected: BTreeMap<String, Vec<String>>,
+}
+
+impl<'a> Enricher<'a> {
+    fn components(&mut self, components: &mut [Component]) {
+        for component in components {
+            self.component(component);
+        }
+    }
+
+    fn component(&mut self, component: &mut Component) {
+        if let Some(purl) = component.purl.as_ref().map(ToString::to_string) {
+            let status = Status::of(&purl, self.found);
+            let mut properties = component.properties.take().map(|p| p.0).unwrap_or_default();
+            properties.retain(|p| p.name != STATUS_PROPERTY);
+            properties.push(Property::new(STATUS_PROPERTY, status_property(status)));
+            component.properties = Some(Properties(properties));
+
+            if let Some(cves) = self.vulnerabilities.get(&normalize(&purl)) {
+                let bom_ref = self.bom_ref(component, &purl);
+                for cve in cves {
+                    self.affected
+                        .entry(cve.clone())
+                        .or_default()
+                        .push(bom_ref.clone());
+                }
+            }
+        }
+
+        if let Some(children) = &mut component.components {
+            self.components(&mut children.0);
+        }
+    }
+
+    /// Get the reference of a component, assigning a unique one if it doesn't have one.
+    fn bom_ref(&mut self, component: &mut Component, purl: &str) -> String {
+        if let Some(bom_ref) = &component.bom_ref {
+            return bom_ref.clone();
+        }
+
+        let mut bom_ref = purl.to_string();
+        let mut n = 1;
+        while self.bom_refs.contains(&bom_ref) {
+            n += 1;
+            bom_ref = format!("{purl}#{n}");
+        }
+
+        self.bom_refs.insert(bom_ref.clone());
+        component.bom_ref = Some(bom_ref.clone());
+        bom_ref
+    }
+}
+
+/// Collect all `bom-ref`s already in use.
+fn bom_refs(components: &[Component], result: &mut HashSet<String>) {
+    for component in components {
+        result.extend(component.bom_ref.clone());
+        if let Some(children) = &component.components {
+            bom_refs(&children.0, result);
+        }
+    }
+}
+
+/// Tag all components with their status and add the vulnerabilities of the found packages.
+fn enrich(mut bom: Bom, refs: &[PackageRef], packages: &[Package]) -> Bom {
+    let found = refs
+        .iter()
+        .map(|r| (normalize(&r.purl), r.trusted.unwrap_or_default()))
+        .collect::<HashMap<_, _>>();
+    let vulnerabilities = packages
+        .iter()
+        .filter_map(|p| {
+            p.purl.as_ref().map(|purl| {
+                (
+                    normalize(purl),
+                    p.vulnerabilities.iter().map(|v| v.cve.clone()).collect(),
+                )
+            })
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut existing = HashSet::new();
+    if let Some(component) = bom.metadata.as_ref().and_then(|m| m.component.as_ref()) {
+        bom_refs(std::slice::from_ref(component), &mut existing);
+    }
+    if let Some(components) = &bom.components {
+        bom_refs(&components.0, &mut existing);
+    }
+
+    let mut enricher = Enricher {
+        found: &found,
+        vulnerabilities: &vulnerabilities,
+        bom_refs: existing,
+        affected: Default::default(),
+    };
+
+    if let Some(component) = bom.metadata.as_mut().and_then(|m| m.component.as_mut()) {
+        enricher.component(component);
+    }
+    if let Some(components) = &mut bom.components {
+        enricher.components(&mut components.0);
+    }
+
+    let mut result = bom.vulnerabilities.take().map(|v| v.0).unwrap_or_default();
+    for (cve, affected) in enricher.affected {
+        // keep what the document already tells about a vulnerability
+        if result
+            .iter()
+            .any(|v| v.id.as_ref().map(ToString::to_string).as_deref() == Some(&cve))
+        {
+            continue;
+        }
+
+        let mut vulnerability = Vulnerability::new(None);
+        vulnerability.id = Some(NormalizedString::new(&cve));
+        vulnerability.vulnerability_targets = Some(VulnerabilityTargets(
+            affected.into_iter().map(VulnerabilityTarget::new).collect(),
+        ));
+        result.push(vulnerability);
+    }
+
+    if !result.is_empty() {
+        bom.vulnerabilities = Some(Vulnerabilities(result));
+    }
+
+    bom
+}
+
+/// Write the enriched SBOM, in the format of the original document.
+///
+/// Vulnerabilities require version 1.4 of the specification, so the result always uses 1.4.
+fn write(bom: Bom, format: Format) -> Result<String, EnrichError> {
+    let mut output = Vec::new();
+    match format {
+        Format::Xml => bom.output_as_xml_v1_4(&mut output)?,
+        _ => bom.output_as_json_v1_4(&mut output)?,
+    }
+    Ok(String::from_utf8(output)?)
+}
+
+/// The name of the enriched file, e.g. `sbom.enriched.json` for `sbom.json`.
+fn file_name(name: &str, format: Format) -> String {
+    let extension = match format {
+        Format::Xml => "xml",
+        _ => "json",
+    };
+    let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(name);
+    format!("{stem}.enriched.{extension}")
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct EnrichedExportProperties {
+    pub files: Rc<Vec<SourceFile>>,
+    pub refs: Vec<PackageRef>,
+    pub packages: Vec<Package>,
+}
+
+/// Download all CycloneDX documents, enriched with the information of the backend.
+#[function_component(EnrichedExport)]
+pub fn enriched_export(props: &EnrichedExportProperties) -> Html {
+    let disabled = !props
+        .files
+        .iter()
+        .any(|file| matches!(file.sbom.source, Source::CycloneDx(_)));
+
+    let onclick = {
+        let props = props.clone();
+        Callback::from(move |_| {
+            for file in props.files.iter() {
+                let bom = match &file.sbom.source {
+                    Source::CycloneDx(bom) => bom.clone(),
+                    Source::Spdx(_) => continue,
+                };
+                let format = file.sbom.r#type.format;
+
+                let result = write(enrich(bom, &props.refs, &props.packages), format)
+                    .map_err(|err| err.to_string())
+                    .and_then(|content| {
+                        let mime_type = match format {
+                            Format::Xml => "application/vnd.cyclonedx+xml",
+                            _ => "application/vnd.cyclonedx+json",
+                        };
+                        save(&file_name(&file.name, format), mime_type, &content)
+                            .map_err(|err| format!("{err:?}"))
+                    });
+
+                if let Err(err) = result {
+                    log::warn!("Failed to export enriched SBOM '{}': {err}", file.name);
+                }
+            }
+        })
+    };
+
+    html!(
+        <Button
+            variant={ButtonVariant::Link}
+            icon={Icon::Download}
+            label="Enriched CycloneDX"
+            {onclick}
+            {disabled}
+        />
+    )
+}
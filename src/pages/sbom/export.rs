@@ -0,0 +1,251 @@
+//! Exporting the results of an inspection.
+
+use super::unknown::UnknownEntry;
+use crate::purl::normalize;
+use itertools::Itertools;
+use packageurl::PackageUrl;
+use patternfly_yew::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use trust_api_model::prelude::*;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use yew::prelude::*;
+
+/// The time (in milliseconds) after which the URL of a download is revoked.
+const REVOKE_TIMEOUT: i32 = 60_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, strum::Display)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Found,
+    Unknown,
+}
+
+/// A single row of the exported results.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ExportEntry {
+    pub purl: String,
+    pub version: Option<String>,
+    pub r#type: Option<String>,
+    pub qualifiers: BTreeMap<String, String>,
+    pub trusted: bool,
+    pub status: Status,
+    pub sources: Vec<String>,
+}
+
+impl ExportEntry {
+    fn new(purl: &str, status: Status) -> Self {
+        let parsed = PackageUrl::from_str(purl).ok();
+        Self {
+            purl: purl.to_string(),
+            version: parsed
+                .as_ref()
+                .and_then(|p| p.version().map(ToString::to_string)),
+            r#type: parsed.as_ref().map(|p| p.ty().to_string()),
+            qualifiers: parsed
+                .as_ref()
+                .map(|p| {
+                    p.qualifiers()
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            trusted: false,
+            status,
+            sources: vec![],
+        }
+    }
+
+    fn qualifiers(&self) -> String {
+        self.qualifiers
+            .iter()
+            .map(|(k, v)| format!("{k}={v}"))
+            .join("&")
+    }
+}
+
+/// Collect the found and unknown packages into a single list of entries.
+pub fn into_export(
+    found: &[PackageRef],
+    unknown: &[UnknownEntry],
+    sources: &BTreeMap<String, Vec<String>>,
+) -> Vec<ExportEntry> {
+    let found = found.iter().map(|pkg| ExportEntry {
+        trusted: pkg.trusted.unwrap_or_default(),
+        sources: sources
+            .get(&normalize(&pkg.purl))
+            .cloned()
+            .unwrap_or_default(),
+        ..ExportEntry::new(&pkg.purl, Status::Found)
+    });
+
+    let unknown = unknown.iter().map(|entry| {
+        let mut result = ExportEntry::new(&entry.purl, Status::Unknown);
+        if result.version.is_none() {
+            result.version = entry.version.clone();
+        }
+        result.sources = entry.sources.clone();
+        result
+    });
+
+    found
+        .chain(unknown)
+        .sorted_by(|a, b| a.purl.cmp(&b.purl))
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumIter)]
+pub enum ExportFormat {
+    #[strum(to_string = "CSV")]
+    Csv,
+    #[strum(to_string = "JSON")]
+    Json,
+    Markdown,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Markdown => "md",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::Json => "application/json",
+            Self::Markdown => "text/markdown",
+        }
+    }
+
+    pub fn render(&self, entries: &[ExportEntry]) -> String {
+        match self {
+            Self::Csv => to_csv(entries),
+            // serializing plain strings, booleans and maps can't fail
+            Self::Json => serde_json::to_string_pretty(entries).unwrap_or_default(),
+            Self::Markdown => to_markdown(entries),
+        }
+    }
+}
+
+fn to_csv(entries: &[ExportEntry]) -> String {
+    fn escape(value: &str) -> String {
+        // spreadsheets run values starting with these as formulas
+        let value = match value.starts_with(['=', '+', '-', '@']) {
+            true => format!("'{value}"),
+            false => value.to_string(),
+        };
+        match value.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", value.replace('"', "\"\"")),
+            false => value,
+        }
+    }
+
+    let mut result = String::from("purl,version,type,qualifiers,trusted,status,sources\n");
+    for entry in entries {
+        let row = [
+            entry.purl.clone(),
+            entry.version.clone().unwrap_or_default(),
+            entry.r#type.clone().unwrap_or_default(),
+            entry.qualifiers(),
+            entry.trusted.to_string(),
+            entry.status.to_string(),
+            entry.sources.join(" "),
+        ];
+        result.push_str(&row.iter().map(|value| escape(value)).join(","));
+        result.push('\n');
+    }
+    result
+}
+
+fn to_markdown(entries: &[ExportEntry]) -> String {
+    fn escape(value: &str) -> String {
+        value.replace('|', "\\|")
+    }
+
+    let found = entries.iter().filter(|e| e.status == Status::Found).count();
+    let trusted = entries.iter().filter(|e| e.trusted).count();
+
+    let mut result = String::from("# SBOM inspection\n\n");
+    result.push_str(&format!("* Packages: {}\n", entries.len()));
+    result.push_str(&format!("* Found: {found}\n"));
+    result.push_str(&format!("* Trusted: {trusted}\n"));
+    result.push_str(&format!("* Unknown: {}\n\n", entries.len() - found));
+
+    result.push_str("| Package URL | Version | Type | Qualifiers | Trusted | Status |\n");
+    result.push_str("| --- | --- | --- | --- | --- | --- |\n");
+    for entry in entries {
+        result.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} |\n",
+            escape(&entry.purl),
+            escape(entry.version.as_deref().unwrap_or_default()),
+            escape(entry.r#type.as_deref().unwrap_or_default()),
+            escape(&entry.qualifiers()),
+            if entry.trusted { "yes" } else { "no" },
+            entry.status,
+        ));
+    }
+    result
+}
+
+/// Offer some content as a file download to the user.
+pub fn save(name: &str, mime_type: &str, content: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(content));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)?;
+
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let a: web_sys::HtmlAnchorElement =
+        gloo_utils::document().create_element("a")?.unchecked_into();
+    a.set_href(&url);
+    a.set_download(name);
+    a.click();
+
+    // revoking the URL right away may cancel the download
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    gloo_utils::window().set_timeout_with_callback_and_timeout_and_arguments_0(
+        revoke.unchecked_ref(),
+        REVOKE_TIMEOUT,
+    )?;
+
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ExportProperties {
+    pub entries: Rc<Vec<ExportEntry>>,
+}
+
+/// Buttons for downloading the results in the different export formats.
+#[function_component(Export)]
+pub fn export(props: &ExportProperties) -> Html {
+    html!(
+        { for ExportFormat::iter().map(|format| {
+            let entries = props.entries.clone();
+            let onclick = Callback::from(move |_| {
+                let name = format!("sbom-inspection.{}", format.extension());
+                if let Err(err) = save(&name, format.mime_type(), &format.render(&entries)) {
+                    log::warn!("Failed to export results: {err:?}");
+                }
+            });
+            html!(
+                <Button
+                    variant={ButtonVariant::Link}
+                    icon={Icon::Download}
+                    label={format.to_string()}
+                    {onclick}
+                />
+            )
+        }) }
+    )
+}
@@ -0,0 +1,105 @@
+use super::model::{Source, SourceFile};
+use crate::purl::normalize;
+use patternfly_yew::prelude::*;
+use std::collections::{BTreeSet, HashSet};
+use std::rc::Rc;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+
+/// Summary of a single inspected file.
+#[derive(Clone, PartialEq)]
+pub struct FileEntry {
+    name: String,
+    title: Option<String>,
+    r#type: String,
+    spec_version: String,
+    components: usize,
+    relationships: usize,
+    found: usize,
+    unknown: usize,
+}
+
+impl TableEntryRenderer for FileEntry {
+    fn render_cell(&self, context: &CellContext) -> Cell {
+        match context.column {
+            0 => html!({ &self.name }),
+            1 => html!({ self.title.clone().unwrap_or_default() }),
+            2 => html!(<>{ &self.r#type } {" "} { &self.spec_version }</>),
+            3 => html!({ self.components }),
+            4 => html!({ self.relationships }),
+            5 => html!({ self.found }),
+            6 => html!({ self.unknown }),
+            _ => html!(),
+        }
+        .into()
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SourceFilesProperties {
+    pub files: Rc<Vec<FileEntry>>,
+}
+
+#[function_component(SourceFiles)]
+pub fn source_files(props: &SourceFilesProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="File"/>
+        <TableColumn label="Name"/>
+        <TableColumn label="Format"/>
+        <TableColumn label="Components"/>
+        <TableColumn label="Relationships"/>
+        <TableColumn label="Found"/>
+        <TableColumn label="Unknown"/>
+      </TableHeader>
+    );
+
+    let entries = use_memo(
+        |entries| SharedTableModel::new((**entries).clone()),
+        props.files.clone(),
+    );
+
+    html!(
+        <Table<SharedTableModel<FileEntry>>
+            {header} entries={(*entries).clone()}
+            mode={TableMode::Compact}
+        >
+        </Table<SharedTableModel<FileEntry>>>
+    )
+}
+
+/// Create the per-file breakdown of found and unknown packages.
+pub fn into_files(files: &[SourceFile], refs: &[PackageRef]) -> Vec<FileEntry> {
+    let found = refs
+        .iter()
+        .map(|r| normalize(&r.purl))
+        .collect::<HashSet<_>>();
+
+    files
+        .iter()
+        .map(|file| {
+            let purls = file.sbom.purls().map(normalize).collect::<BTreeSet<_>>();
+            let found = purls.iter().filter(|purl| found.contains(*purl)).count();
+
+            let title = match &file.sbom.source {
+                Source::CycloneDx(bom) => bom
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.component.as_ref())
+                    .map(|c| c.name.to_string()),
+                Source::Spdx(doc) => doc.name.clone(),
+            };
+
+            FileEntry {
+                name: file.name.clone(),
+                title,
+                r#type: file.sbom.r#type.to_string(),
+                spec_version: file.sbom.spec_version.clone(),
+                components: file.sbom.components.len(),
+                relationships: file.sbom.relationships.len(),
+                found,
+                unknown: purls.len() - found,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,306 @@
+//! History of inspected SBOMs, persisted in the browser's IndexedDB.
+//!
+//! The summaries are kept apart from the (potentially large) documents, so that listing the
+//! history doesn't need to load all documents.
+
+use super::upload::UploadedContent;
+use patternfly_yew::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+use yew::prelude::*;
+use yew_more_hooks::hooks::r#async::*;
+
+const DATABASE: &str = "sbom-history";
+const DATABASE_VERSION: u32 = 1;
+const SUMMARIES: &str = "summaries";
+const DOCUMENTS: &str = "documents";
+
+/// The number of inspections we keep.
+const MAX_ENTRIES: usize = 20;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("The browser doesn't support IndexedDB")]
+    Unavailable,
+    #[error("Failed to access the history: {0}")]
+    Storage(String),
+    #[error("Failed to decode history entry: {0}")]
+    Decode(#[from] serde_json::Error),
+}
+
+impl From<JsValue> for HistoryError {
+    fn from(value: JsValue) -> Self {
+        Self::Storage(value.as_string().unwrap_or_else(|| format!("{value:?}")))
+    }
+}
+
+/// Summary of a past inspection.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Summary {
+    pub id: String,
+    pub name: String,
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+    pub components: usize,
+    pub found: usize,
+    pub unknown: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    name: String,
+    content: String,
+}
+
+impl Summary {
+    fn time(&self) -> String {
+        js_sys::Date::new(&JsValue::from_f64(self.timestamp as f64))
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+/// The current time, in milliseconds since the epoch.
+pub fn now() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// Create a new identifier for an inspection.
+pub fn new_id() -> String {
+    now().to_string()
+}
+
+/// Wait for an IndexedDB request to finish.
+async fn request(request: &IdbRequest) -> Result<JsValue, HistoryError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let onsuccess = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                let _ = resolve.call1(&JsValue::UNDEFINED, &result);
+            })
+        };
+        let onerror = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let error = request
+                    .error()
+                    .ok()
+                    .flatten()
+                    .map(|err| JsValue::from(err.message()))
+                    .unwrap_or(JsValue::UNDEFINED);
+                let _ = reject.call1(&JsValue::UNDEFINED, &error);
+            })
+        };
+        request.set_onsuccess(Some(onsuccess.unchecked_ref()));
+        request.set_onerror(Some(onerror.unchecked_ref()));
+    });
+
+    Ok(JsFuture::from(promise).await?)
+}
+
+async fn open() -> Result<IdbDatabase, HistoryError> {
+    let factory = gloo_utils::window()
+        .indexed_db()?
+        .ok_or(HistoryError::Unavailable)?;
+
+    let open = factory.open_with_u32(DATABASE, DATABASE_VERSION)?;
+    let onupgradeneeded = {
+        let open = open.clone();
+        Closure::once_into_js(move || {
+            if let Ok(db) = open.result() {
+                let db: IdbDatabase = db.unchecked_into();
+                let names = db.object_store_names();
+                for store in [SUMMARIES, DOCUMENTS] {
+                    if !names.contains(store) {
+                        let _ = db.create_object_store(store);
+                    }
+                }
+            }
+        })
+    };
+    open.set_onupgradeneeded(Some(onupgradeneeded.unchecked_ref()));
+
+    Ok(request(&open).await?.unchecked_into())
+}
+
+fn store(
+    db: &IdbDatabase,
+    name: &str,
+    mode: IdbTransactionMode,
+) -> Result<IdbObjectStore, HistoryError> {
+    Ok(db
+        .transaction_with_str_and_mode(name, mode)?
+        .object_store(name)?)
+}
+
+fn decode<T: DeserializeOwned>(value: JsValue) -> Result<T, HistoryError> {
+    let value = value
+        .as_string()
+        .ok_or_else(|| HistoryError::Storage("Unexpected value type".to_string()))?;
+    Ok(serde_json::from_str(&value)?)
+}
+
+/// List all past inspections, newest first.
+pub async fn list() -> Result<Vec<Summary>, HistoryError> {
+    let db = open().await?;
+    let summaries = store(&db, SUMMARIES, IdbTransactionMode::Readonly)?;
+    let values: js_sys::Array = request(&summaries.get_all()?).await?.unchecked_into();
+
+    let mut result = values
+        .iter()
+        .map(decode::<Summary>)
+        .collect::<Result<Vec<_>, _>>()?;
+    result.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(result)
+}
+
+/// Load the documents of a past inspection.
+pub async fn load(id: &str) -> Result<Vec<UploadedContent>, HistoryError> {
+    let db = open().await?;
+    let documents = store(&db, DOCUMENTS, IdbTransactionMode::Readonly)?;
+    let value = request(&documents.get(&JsValue::from_str(id))?).await?;
+    if value.is_undefined() {
+        return Err(HistoryError::Storage(format!("Unknown entry: {id}")));
+    }
+
+    Ok(decode::<Vec<Document>>(value)?
+        .into_iter()
+        .map(|d| UploadedContent {
+            name: d.name,
+            content: d.content,
+        })
+        .collect())
+}
+
+/// Store an inspection, replacing an existing entry with the same ID and dropping the oldest
+/// entries exceeding the limit.
+pub async fn save(summary: Summary, documents: &[UploadedContent]) -> Result<(), HistoryError> {
+    let key = JsValue::from_str(&summary.id);
+    let summary = serde_json::to_string(&summary)?;
+    let documents = serde_json::to_string(
+        &documents
+            .iter()
+            .map(|d| Document {
+                name: d.name.clone(),
+                content: d.content.clone(),
+            })
+            .collect::<Vec<_>>(),
+    )?;
+
+    let db = open().await?;
+    request(
+        &store(&db, DOCUMENTS, IdbTransactionMode::Readwrite)?
+            .put_with_key(&documents.into(), &key)?,
+    )
+    .await?;
+    request(
+        &store(&db, SUMMARIES, IdbTransactionMode::Readwrite)?
+            .put_with_key(&summary.into(), &key)?,
+    )
+    .await?;
+
+    for old in list().await?.iter().skip(MAX_ENTRIES) {
+        remove(&old.id).await?;
+    }
+
+    Ok(())
+}
+
+/// Remove a past inspection.
+pub async fn remove(id: &str) -> Result<(), HistoryError> {
+    let key = JsValue::from_str(id);
+    let db = open().await?;
+    request(&store(&db, SUMMARIES, IdbTransactionMode::Readwrite)?.delete(&key)?).await?;
+    request(&store(&db, DOCUMENTS, IdbTransactionMode::Readwrite)?.delete(&key)?).await?;
+    Ok(())
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct RecentInspectionsProperties {
+    /// Called with the ID and the documents of the inspection to reopen
+    pub onopen: Callback<(String, Vec<UploadedContent>)>,
+}
+
+#[function_component(RecentInspections)]
+pub fn recent_inspections(props: &RecentInspectionsProperties) -> Html {
+    let entries = use_async_with_options(
+        async { list().await.map_err(|err| err.to_string()) },
+        UseAsyncOptions::enable_auto(),
+    );
+
+    let error = use_state_eq(|| None::<String>);
+
+    let onopen = |id: String| {
+        let onopen = props.onopen.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let onopen = onopen.clone();
+            let error = error.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match load(&id).await {
+                    Ok(documents) => onopen.emit((id, documents)),
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            });
+        })
+    };
+
+    let onremove = |id: String| {
+        let entries = entries.clone();
+        let error = error.clone();
+        Callback::from(move |_| {
+            let entries = entries.clone();
+            let error = error.clone();
+            let id = id.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match remove(&id).await {
+                    Ok(()) => entries.run(),
+                    Err(err) => error.set(Some(err.to_string())),
+                }
+            });
+        })
+    };
+
+    html!(
+        <Card
+            title={html!(<Title>{"Recent inspections"}</Title>)}
+        >
+            if let Some(error) = &*error {
+                <Alert r#type={AlertType::Danger} title="Failed to access the history" inline=true>
+                    { error }
+                </Alert>
+            }
+            {
+                match &*entries {
+                    UseAsyncState::Pending | UseAsyncState::Processing => html!(<Spinner/>),
+                    UseAsyncState::Ready(Ok(entries)) if entries.is_empty() => html!(
+                        <Content><p>{ "No SBOMs inspected so far." }</p></Content>
+                    ),
+                    UseAsyncState::Ready(Ok(entries)) => html!(
+                        <List r#type={ListType::Plain}>
+                            { for entries.iter().map(|entry| html!(
+                                <>
+                                    <Button variant={ButtonVariant::Link} label={entry.name.clone()} onclick={onopen(entry.id.clone())} />
+                                    {" "}
+                                    <Button variant={ButtonVariant::Plain} icon={Icon::Times} onclick={onremove(entry.id.clone())} />
+                                    <br />
+                                    <small>
+                                        { entry.time() } { " · " }
+                                        { entry.components } { " components, " }
+                                        { entry.found } { " found, " }
+                                        { entry.unknown } { " unknown" }
+                                    </small>
+                                </>
+                            )) }
+                        </List>
+                    ),
+                    UseAsyncState::Ready(Err(err)) => html!(<>{"Failed to load: "} { err } </>),
+                }
+            }
+        </Card>
+    )
+}
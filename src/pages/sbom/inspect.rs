@@ -1,24 +1,36 @@
 use super::{
+    enrich::EnrichedExport,
+    export::{into_export, Export},
+    files::{into_files, SourceFiles},
+    history,
+    licenses::{into_licenses, Licenses},
+    model::{self, SourceFile},
+    raw::RawDocument,
+    search::{search_content, use_chunked_lookup, use_chunked_search},
+    share::Share,
+    suggestions::{into_suggestions, untrusted_packages},
+    tree::{into_tree, DependencyTreeView},
+    unidentified::{into_unidentified, UnidentifiedPackages},
     unknown::{into_unknown, UnknownPackages},
+    upload::UploadedContent,
+    vulnerabilities::{into_vulnerabilities, Vulnerabilities},
     CommonHeader,
 };
 use crate::{
-    backend::PackageService,
     components::{count_title, deps::PackageReferences},
     hooks::use_backend,
+    purl::normalize,
 };
-use cyclonedx_bom::prelude::Bom;
-use packageurl::PackageUrl;
 use patternfly_yew::prelude::*;
+use std::collections::BTreeSet;
 use std::rc::Rc;
-use std::str::FromStr;
 use yew::prelude::*;
-use yew_more_hooks::hooks::r#async::*;
 
 #[derive(Clone, PartialEq, Properties)]
 pub struct InspectProperties {
-    pub raw: Rc<String>,
-    pub bom: Rc<Bom>,
+    /// The ID of the inspection in the history
+    pub id: String,
+    pub files: Rc<Vec<SourceFile>>,
 }
 
 #[function_component(Inspect)]
@@ -31,84 +43,252 @@ pub fn inspect(props: &InspectProperties) -> Html {
         })
     };
 
+    // show a component in the raw document it was found in
+    let backdrop = use_backdrop();
+    let onselect_source = {
+        let files = props.files.clone();
+        Callback::from(move |(name, purl): (String, String)| {
+            let file = match files.iter().find(|file| file.name == name) {
+                Some(file) => file,
+                None => return,
+            };
+            if let Some(backdrop) = &backdrop {
+                backdrop.open(html!(
+                    <Bullseye plain=true>
+                        <Modal title={name} variant={ModalVariant::Large}>
+                            <RawDocument raw={file.raw.clone()} format={file.sbom.r#type.format} target={purl} />
+                        </Modal>
+                    </Bullseye>
+                ));
+            }
+        })
+    };
+
     let purls = use_memo(
-        |sbom| match &sbom.components {
-            Some(comps) => comps
-                .0
+        |files| {
+            files
                 .iter()
-                .filter_map(|c| c.purl.as_ref().map(|p| p.to_string()))
-                .collect(),
-            None => vec![],
+                .flat_map(|file| file.sbom.purls())
+                .map(normalize)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>()
         },
-        props.bom.clone(),
+        props.files.clone(),
     );
 
+    let sources = use_memo(|files| model::sources(files), props.files.clone());
+    let originals = use_memo(|files| model::originals(files), props.files.clone());
+
     let backend = use_backend();
 
-    let service = use_memo(
-        |backend| PackageService::new((**backend).clone()),
-        backend.clone(),
+    let fetch = use_chunked_search(purls.clone());
+
+    let untrusted = use_memo(
+        |f| {
+            f.as_ref()
+                .map(|f| untrusted_packages(f))
+                .unwrap_or_default()
+        },
+        fetch.data().cloned(),
     );
 
-    let fetch = {
-        let service = service.clone();
-        use_async_with_cloned_deps(
-            |purls| async move {
-                service
-                    .search(
-                        purls
-                            .iter()
-                            .filter_map(|purl| PackageUrl::from_str(purl).ok()),
-                    )
-                    .await
-            },
-            purls.clone(),
-        )
-    };
+    // search all versions of untrusted packages, to find trusted replacements
+    let fetch_trusted = use_chunked_search(untrusted);
+
+    let suggestions = use_memo(
+        |(f, versions)| match (f, versions) {
+            (Some(f), Some(versions)) => Some(Rc::new(into_suggestions(f, versions))),
+            _ => None,
+        },
+        (fetch.data().cloned(), fetch_trusted.data().cloned()),
+    );
 
     let unknown = use_memo(
-        |(f, bom)| match f {
-            Some(data) => into_unknown(&bom, data),
+        |(f, files)| match f {
+            Some(data) => into_unknown(files, data),
             None => vec![],
         },
-        (fetch.data().cloned(), props.bom.clone()),
+        (fetch.data().cloned(), props.files.clone()),
     );
 
-    match &*fetch {
-        UseAsyncState::Pending | UseAsyncState::Processing => html!(
-            <PageSection fill={PageSectionFill::Fill}>
-                <Spinner />
-            </PageSection>
+    use_effect_with_deps(
+        |(id, files, found, unknown)| {
+            // remember the inspection, once we know what we found
+            if let Some(found) = *found {
+                let summary = history::Summary {
+                    id: id.clone(),
+                    name: files
+                        .iter()
+                        .map(|file| file.name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    timestamp: history::now(),
+                    components: files.iter().map(|file| file.sbom.components.len()).sum(),
+                    found,
+                    unknown: *unknown,
+                };
+                let documents = files
+                    .iter()
+                    .map(|file| UploadedContent {
+                        name: file.name.clone(),
+                        content: (*file.raw).clone(),
+                    })
+                    .collect::<Vec<_>>();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(err) = history::save(summary, &documents).await {
+                        log::warn!("Failed to store inspection: {err}");
+                    }
+                });
+            }
+        },
+        (
+            props.id.clone(),
+            props.files.clone(),
+            fetch.data().map(|f| f.len()),
+            unknown.len(),
         ),
-        UseAsyncState::Ready(Ok(data)) => html!(
+    );
+
+    let export = use_memo(
+        |(f, unknown, sources)| {
+            let found = f.as_ref().map(|f| &f.0[..]).unwrap_or_default();
+            into_export(found, unknown, sources)
+        },
+        (fetch.data().cloned(), unknown.clone(), sources.clone()),
+    );
+
+    let licenses = use_memo(
+        |(files, policy)| into_licenses(files, policy),
+        (props.files.clone(), backend.licenses.clone()),
+    );
+
+    let unidentified = use_memo(|files| into_unidentified(files), props.files.clone());
+
+    let tree = use_memo(
+        |(f, files)| {
+            let refs = f.as_ref().map(|f| &f.0[..]).unwrap_or_default();
+            Rc::new(into_tree(files, refs))
+        },
+        (fetch.data().cloned(), props.files.clone()),
+    );
+
+    // the full information of the found packages, once they are known
+    let found = use_memo(
+        |found| {
+            found.as_ref().map(|found| {
+                Rc::new(
+                    found
+                        .0
+                        .iter()
+                        .map(|pkg| pkg.purl.clone())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        },
+        fetch.data().cloned(),
+    );
+    let fetch_packages = use_chunked_lookup((*found).clone());
+
+    let vulnerabilities = use_memo(
+        |(f, files)| match f {
+            Some(packages) => into_vulnerabilities(files, packages),
+            None => vec![],
+        },
+        (fetch_packages.data().cloned(), props.files.clone()),
+    );
+
+    let vulnerabilities_title = match fetch_packages.data() {
+        Some(_) => count_title(vulnerabilities.len(), "Vulnerability", "Vulnerabilities"),
+        None => "Vulnerabilities".to_string(),
+    };
+
+    let file_entries = use_memo(
+        |(f, files)| match f {
+            Some(data) => into_files(files, data),
+            None => vec![],
+        },
+        (fetch.data().cloned(), props.files.clone()),
+    );
+
+    search_content(&fetch, |data| {
+        html!(
             <>
                 <CommonHeader />
 
+                <PageSection variant={PageSectionVariant::Light}>
+                    <SourceFiles files={file_entries} />
+                    if let Some(suggestions) = &*suggestions {
+                        if !suggestions.is_empty() {
+                            <Alert
+                                r#type={AlertType::Info}
+                                inline=true
+                                title={format!("{} could be replaced by trusted builds", count_title(suggestions.len(), "found component", "found components"))}
+                            />
+                        }
+                    }
+                </PageSection>
+
                 <PageSection r#type={PageSectionType::Tabs} variant={PageSectionVariant::Light} sticky={[PageSectionSticky::Top]}>
-                    <Tabs inset={TabInset::Page} detached=true {onselect}>
-                        <Tab label={count_title(data.len(), "Found", "Found")} />
-                        <Tab label={count_title(unknown.len(), "Unknown", "Unknown")} />
-                        <Tab label="Raw SBOM"/>
-                    </Tabs>
+                    <Split>
+                        <SplitItem fill=true>
+                            <Tabs inset={TabInset::Page} detached=true {onselect}>
+                                <Tab label={count_title(data.len(), "Found", "Found")} />
+                                <Tab label={count_title(unknown.len(), "Unknown", "Unknown")} />
+                                <Tab label={count_title(unidentified.len(), "Unidentified", "Unidentified")} />
+                                <Tab label={vulnerabilities_title} />
+                                <Tab label={count_title(licenses.len(), "License", "Licenses")} />
+                                <Tab label="Dependency Tree"/>
+                                <Tab label="Raw SBOM"/>
+                            </Tabs>
+                        </SplitItem>
+                        <SplitItem>
+                            <Share files={props.files.clone()} />
+                            <Export entries={export.clone()} />
+                            if let Some(packages) = fetch_packages.data() {
+                                <EnrichedExport files={props.files.clone()} refs={data.0.clone()} packages={packages.clone()} />
+                            }
+                        </SplitItem>
+                    </Split>
                 </PageSection>
 
                 <PageSection hidden={*tab != 0} fill={PageSectionFill::Fill}>
-                    <PackageReferences refs={data.0.clone()} />
+                    <PackageReferences refs={data.0.clone()} sources={sources.clone()} originals={originals.clone()} suggestions={(*suggestions).clone()} onselect={onselect_source.clone()} />
                 </PageSection>
 
                 <PageSection hidden={*tab != 1} fill={PageSectionFill::Fill}>
-                    <UnknownPackages {unknown} />
+                    <UnknownPackages {unknown} onselect={onselect_source.clone()} />
+                </PageSection>
+
+                <PageSection hidden={*tab != 2} fill={PageSectionFill::Fill}>
+                    <UnidentifiedPackages {unidentified} />
+                </PageSection>
+
+                <PageSection hidden={*tab != 3} fill={PageSectionFill::Fill}>
+                    { search_content(&fetch_packages, |_| html!(
+                        <Vulnerabilities vulnerabilities={vulnerabilities.clone()} />
+                    )) }
+                </PageSection>
+
+                <PageSection hidden={*tab != 4} fill={PageSectionFill::Fill}>
+                    <Licenses {licenses} />
+                </PageSection>
+
+                <PageSection hidden={*tab != 5} fill={PageSectionFill::Fill}>
+                    <DependencyTreeView tree={(*tree).clone()} />
                 </PageSection>
 
-                <PageSection hidden={*tab != 2} variant={PageSectionVariant::Light} fill={PageSectionFill::Fill}>
-                    <CodeBlock>
-                        <CodeBlockCode>
-                            { &props.raw }
-                        </CodeBlockCode>
-                    </CodeBlock>
-               </PageSection>
+                <PageSection hidden={*tab != 6} variant={PageSectionVariant::Light} fill={PageSectionFill::Fill}>
+                    { for props.files.iter().map(|file| html!(
+                        <>
+                            if props.files.len() > 1 {
+                                <Title level={Level::H3}>{ &file.name }</Title>
+                            }
+                            <RawDocument raw={file.raw.clone()} format={file.sbom.r#type.format} />
+                        </>
+                    )) }
+                </PageSection>
             </>
-        ),
-        UseAsyncState::Ready(Err(err)) => html!(<>{"Failed to load: "} { err } </>),
-    }
+        )
+    })
 }
@@ -0,0 +1,275 @@
+//! Checking the licenses of components against the license policy of the deployment.
+
+use super::model::{License, SourceFile};
+use crate::backend::LicensePolicy;
+use patternfly_yew::prelude::*;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// The number of component names shown per license.
+const MAX_COMPONENTS: usize = 10;
+
+/// The result of checking a license, ordered from worst to best.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LicenseStatus {
+    Denied,
+    Missing,
+    Unknown,
+    NotListed,
+    Allowed,
+}
+
+impl LicenseStatus {
+    fn label(&self) -> Html {
+        let (label, color) = match self {
+            Self::Denied => ("Denied", Color::Red),
+            Self::Missing => ("Missing", Color::Orange),
+            Self::Unknown => ("Unknown", Color::Orange),
+            Self::NotListed => ("Not listed", Color::Grey),
+            Self::Allowed => ("Allowed", Color::Green),
+        };
+        html!(<Label {label} {color} />)
+    }
+}
+
+/// Evaluates an SPDX license expression against the policy.
+struct Evaluator<'a> {
+    tokens: Vec<String>,
+    pos: usize,
+    policy: &'a LicensePolicy,
+}
+
+impl<'a> Evaluator<'a> {
+    fn new(expression: &str, policy: &'a LicensePolicy) -> Self {
+        let tokens = expression
+            .replace('(', " ( ")
+            .replace(')', " ) ")
+            .split_whitespace()
+            .map(ToString::to_string)
+            .collect();
+
+        Self {
+            tokens,
+            pos: 0,
+            policy,
+        }
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_operator(&self, operator: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(token) if token.eq_ignore_ascii_case(operator))
+    }
+
+    /// Evaluate the whole expression, `None` if it isn't a valid expression.
+    fn evaluate(mut self) -> Option<LicenseStatus> {
+        let result = self.or()?;
+        match self.pos == self.tokens.len() {
+            true => Some(result),
+            false => None,
+        }
+    }
+
+    /// One of the alternatives is sufficient, so the best one counts.
+    fn or(&mut self) -> Option<LicenseStatus> {
+        let mut result = self.and()?;
+        while self.peek_operator("OR") {
+            self.pos += 1;
+            result = result.max(self.and()?);
+        }
+        Some(result)
+    }
+
+    /// All licenses must be fulfilled, so the worst one counts.
+    fn and(&mut self) -> Option<LicenseStatus> {
+        let mut result = self.license()?;
+        while self.peek_operator("AND") {
+            self.pos += 1;
+            result = result.min(self.license()?);
+        }
+        Some(result)
+    }
+
+    fn license(&mut self) -> Option<LicenseStatus> {
+        match self.next()?.as_str() {
+            "(" => {
+                let result = self.or()?;
+                match self.next()?.as_str() {
+                    ")" => Some(result),
+                    _ => None,
+                }
+            }
+            ")" => None,
+            id => {
+                if self.peek_operator("WITH") {
+                    // the exception doesn't change the license
+                    self.pos += 1;
+                    self.next()?;
+                }
+                Some(self.check(id))
+            }
+        }
+    }
+
+    fn check(&self, id: &str) -> LicenseStatus {
+        let matches = |list: &[String]| {
+            list.iter().any(|entry| {
+                entry.eq_ignore_ascii_case(id)
+                    || id
+                        .strip_suffix('+')
+                        .map(|id| entry.eq_ignore_ascii_case(id))
+                        .unwrap_or_default()
+            })
+        };
+
+        if matches(&self.policy.deny) {
+            LicenseStatus::Denied
+        } else if matches(&self.policy.allow) {
+            LicenseStatus::Allowed
+        } else {
+            LicenseStatus::NotListed
+        }
+    }
+}
+
+/// A license expression, together with the components using it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LicenseEntry {
+    expression: Option<String>,
+    /// If the expression contains license names, and not only SPDX identifiers
+    named: bool,
+    status: LicenseStatus,
+    components: Vec<String>,
+}
+
+impl TableEntryRenderer for LicenseEntry {
+    fn render_cell(&self, context: &CellContext) -> Cell {
+        match context.column {
+            0 => match &self.expression {
+                Some(expression) if self.named => html!(
+                    <>{ expression }<small>{ " (license name)" }</small></>
+                ),
+                Some(expression) => html!(<code>{ expression }</code>),
+                None => html!(<i>{ "No license" }</i>),
+            },
+            1 => self.status.label(),
+            2 => html!({ self.components.len() }),
+            3 => html!(
+                <>
+                    { self.components.iter().take(MAX_COMPONENTS).cloned().collect::<Vec<_>>().join(", ") }
+                    if self.components.len() > MAX_COMPONENTS {
+                        { format!(", … and {} more", self.components.len() - MAX_COMPONENTS) }
+                    }
+                </>
+            ),
+            _ => html!(),
+        }
+        .into()
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct LicensesProperties {
+    pub licenses: Rc<Vec<LicenseEntry>>,
+}
+
+#[function_component(Licenses)]
+pub fn licenses(props: &LicensesProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="License"/>
+        <TableColumn label="Status"/>
+        <TableColumn label="Components"/>
+        <TableColumn/>
+      </TableHeader>
+    );
+
+    let entries = use_memo(
+        |entries| SharedTableModel::new((**entries).clone()),
+        props.licenses.clone(),
+    );
+
+    html!(
+        <Table<SharedTableModel<LicenseEntry>>
+            {header} entries={(*entries).clone()}
+            mode={TableMode::Compact}
+        >
+        </Table<SharedTableModel<LicenseEntry>>>
+    )
+}
+
+/// Combine all licenses of a component into a single expression.
+fn expression(licenses: &[License]) -> Option<String> {
+    let licenses = licenses
+        .iter()
+        .map(|license| match license {
+            License::Spdx(license) | License::Name(license) => license.as_str(),
+        })
+        .collect::<Vec<_>>();
+
+    match licenses.as_slice() {
+        [] => None,
+        [license] => Some(license.to_string()),
+        licenses => Some(
+            licenses
+                .iter()
+                .map(|license| match license.contains(char::is_whitespace) {
+                    true => format!("({license})"),
+                    false => license.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" AND "),
+        ),
+    }
+}
+
+/// Group all components by their license, checking each license against the policy.
+///
+/// Expressions containing license names are grouped apart from the same text of SPDX identifiers,
+/// as only the latter can be checked.
+pub fn into_licenses(files: &[SourceFile], policy: &LicensePolicy) -> Vec<LicenseEntry> {
+    let mut groups = BTreeMap::<(Option<String>, bool), (LicenseStatus, Vec<String>)>::new();
+
+    for component in files.iter().flat_map(|file| file.sbom.components.iter()) {
+        let expression = expression(&component.licenses);
+        let named = component
+            .licenses
+            .iter()
+            .any(|l| matches!(l, License::Name(_)));
+        let (_, components) = groups
+            .entry((expression.clone(), named))
+            .or_insert_with(|| {
+                let status = match &expression {
+                    None => LicenseStatus::Missing,
+                    Some(_) if named => LicenseStatus::Unknown,
+                    Some(expression) => Evaluator::new(expression, policy)
+                        .evaluate()
+                        .unwrap_or(LicenseStatus::Unknown),
+                };
+                (status, vec![])
+            });
+        components.push(component.full_name());
+    }
+
+    let mut result = groups
+        .into_iter()
+        .map(|((expression, named), (status, components))| LicenseEntry {
+            expression,
+            named,
+            status,
+            components,
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| {
+        a.status
+            .cmp(&b.status)
+            .then_with(|| a.expression.cmp(&b.expression))
+            .then_with(|| a.named.cmp(&b.named))
+    });
+    result
+}
@@ -1,71 +1,186 @@
-use cyclonedx_bom::prelude::*;
 use patternfly_yew::prelude::*;
 use std::rc::Rc;
 use yew::prelude::*;
 
+mod compare;
+mod downgrade;
+mod download;
+mod enrich;
+mod export;
+mod files;
+mod history;
 mod inspect;
+mod licenses;
+mod model;
+mod parse;
+mod raw;
+mod search;
+mod share;
+mod spdx;
+mod suggestions;
+mod tree;
+mod unidentified;
 mod unknown;
 mod upload;
+mod validate;
+mod vulnerabilities;
 
+use compare::Compare;
+use history::RecentInspections;
 use inspect::Inspect;
-use upload::Upload;
+use model::SourceFile;
+pub use share::SharedSBOM;
+use upload::{Upload, UploadedContent};
+
+/// Parse all uploaded documents, `None` if one of them fails to parse.
+fn parse_files(documents: &[UploadedContent]) -> Option<Rc<Vec<SourceFile>>> {
+    documents
+        .iter()
+        .map(|document| {
+            parse::parse(&document.content).ok().map(|sbom| SourceFile {
+                name: document.name.clone(),
+                raw: Rc::new(document.content.clone()),
+                sbom: Rc::new(sbom),
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(Rc::new)
+}
 
 #[function_component(SBOM)]
 pub fn sbom() -> Html {
-    let content = use_state_eq(|| None::<String>);
+    // the history ID and the documents of the inspection
+    let content = use_state_eq(|| None::<(String, Vec<UploadedContent>)>);
+    let old = use_state_eq(|| None::<Vec<UploadedContent>>);
+    let new = use_state_eq(|| None::<Vec<UploadedContent>>);
+
+    let files = use_memo(
+        |content| {
+            content
+                .as_ref()
+                .and_then(|(id, documents)| parse_files(documents).map(|files| (id.clone(), files)))
+        },
+        content.clone(),
+    );
+
+    let compare = use_memo(
+        |(old, new)| match (old.as_deref(), new.as_deref()) {
+            (Some(old), Some(new)) => parse_files(old).zip(parse_files(new)),
+            _ => None,
+        },
+        ((*old).clone(), (*new).clone()),
+    );
+
+    if let Some((id, files)) = files.as_ref() {
+        return html!(<Inspect id={id.clone()} files={files.clone()} />);
+    }
+
+    if let Some((old, new)) = compare.as_ref() {
+        return html!(<Compare old={old.clone()} new={new.clone()} />);
+    }
+
+    let onvalidate = Callback::from(|data: String| validate::validate(&data));
 
     let onsubmit = {
         let content = content.clone();
         Callback::from(move |data| {
-            content.set(Some(data));
+            content.set(Some((history::new_id(), data)));
         })
     };
 
-    let sbom = use_memo(
-        |content| {
-            content.as_ref().and_then(|data| {
-                Bom::parse_from_json_v1_3(data.as_bytes())
-                    .ok()
-                    .map(|sbom| (Rc::new(data.clone()), Rc::new(sbom)))
-            })
-        },
-        content.clone(),
-    );
+    let onopen = {
+        let content = content.clone();
+        Callback::from(move |entry| {
+            content.set(Some(entry));
+        })
+    };
 
-    match sbom.as_ref() {
-        Some((raw, bom)) => {
-            html!(<Inspect raw={raw.clone()} bom={bom.clone()} />)
-        }
-        None => {
-            let onvalidate =
-                Callback::from(
-                    |data: String| match Bom::parse_from_json_v1_3(data.as_bytes()) {
-                        Ok(_sbom) => Ok(data),
-                        Err(err) => Err(format!("Failed to parse SBOM: {err}")),
-                    },
-                );
-
-            html!(
-                <>
-                    <CommonHeader />
-                    <PageSection variant={PageSectionVariant::Default} fill=true>
-                        <Grid gutter=true>
-                            <GridItem cols={[8]}>
-                                <Card
-                                    title={html!(<Title> {"SBOM content"} </Title>)}
-                                >
-                                    <Upload {onsubmit} {onvalidate}/>
-                                </Card>
-                            </GridItem>
-                            <GridItem cols={[4]}>
+    html!(
+        <>
+            <CommonHeader />
+            <PageSection variant={PageSectionVariant::Default} fill=true>
+                <Grid gutter=true>
+                    <GridItem cols={[8]}>
+                        <Card
+                            title={html!(<Title> {"SBOM content"} </Title>)}
+                        >
+                            <Tabs r#box=true>
+                                <Tab label="Inspect">
+                                    <Upload {onsubmit} onvalidate={onvalidate.clone()}/>
+                                </Tab>
+                                <Tab label="Compare">
+                                    <Grid gutter=true>
+                                        <GridItem cols={[6]}>
+                                            <CompareSide label="Old release" state={old} onvalidate={onvalidate.clone()} />
+                                        </GridItem>
+                                        <GridItem cols={[6]}>
+                                            <CompareSide label="New release" state={new} {onvalidate} />
+                                        </GridItem>
+                                    </Grid>
+                                </Tab>
+                            </Tabs>
+                        </Card>
+                    </GridItem>
+                    <GridItem cols={[4]}>
+                        <Stack gutter=true>
+                            <StackItem>
                                 <GenerateCard />
-                            </GridItem>
-                        </Grid>
-                    </PageSection>
-                </>
-            )
-        }
-    }
+                            </StackItem>
+                            <StackItem>
+                                <RecentInspections {onopen} />
+                            </StackItem>
+                        </Stack>
+                    </GridItem>
+                </Grid>
+            </PageSection>
+        </>
+    )
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct CompareSideProperties {
+    label: AttrValue,
+    state: UseStateHandle<Option<Vec<UploadedContent>>>,
+    onvalidate: Callback<String, Vec<validate::Issue>>,
+}
+
+/// One of the two sides to compare, waiting for the other side once selected.
+#[function_component(CompareSide)]
+fn compare_side(props: &CompareSideProperties) -> Html {
+    let onsubmit = {
+        let state = props.state.clone();
+        Callback::from(move |data| {
+            state.set(Some(data));
+        })
+    };
+
+    let onchange = {
+        let state = props.state.clone();
+        Callback::from(move |_| {
+            state.set(None);
+        })
+    };
+
+    html!(
+        <>
+            <Title level={Level::H3}>{ &props.label }</Title>
+            {
+                match &*props.state {
+                    Some(documents) => html!(
+                        <Content>
+                            <p>
+                                { for documents.iter().map(|d| html!(<><code>{ &d.name }</code>{" "}</>)) }
+                                <Button variant={ButtonVariant::Link} label="Change" onclick={onchange} />
+                            </p>
+                        </Content>
+                    ),
+                    None => html!(
+                        <Upload {onsubmit} onvalidate={props.onvalidate.clone()} submit_label="Select" />
+                    ),
+                }
+            }
+        </>
+    )
 }
 
 #[function_component(CommonHeader)]
@@ -82,7 +197,7 @@ fn common_header() -> Html {
 
 #[function_component(GenerateCard)]
 fn generate_card() -> Html {
-    let maven = r#"mvn org.cyclonedx:cyclonedx-maven-plugin:2.7.7:makeAggregateBom -Dcyclonedx.skipAttach=true -DoutputFormat=json -DschemaVersion=1.3 -Dcyclonedx.verbose=false"#;
+    let maven = r#"mvn org.cyclonedx:cyclonedx-maven-plugin:2.7.7:makeAggregateBom -Dcyclonedx.skipAttach=true -DoutputFormat=json -Dcyclonedx.verbose=false"#;
     let container = r#"syft packages <container> -o cyclonedx-json --file sbom.json"#;
     let container_example =
         r#"syft packages quay.io/keycloak/keycloak:latest -o cyclonedx-json --file sbom.json"#;
@@ -0,0 +1,327 @@
+//! A format neutral view on an SBOM.
+
+use super::{
+    parse::{DocumentType, SpecVersion},
+    spdx,
+};
+use crate::purl::normalize;
+use cyclonedx_bom::{
+    models::license::{LicenseChoice, LicenseIdentifier},
+    prelude::Bom,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+
+/// A parsed SBOM, independent of the format it came in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sbom {
+    pub r#type: DocumentType,
+    /// The version of the specification the document uses
+    pub spec_version: String,
+    pub components: Vec<Component>,
+    pub relationships: Vec<Relationship>,
+    /// The components the document describes
+    pub roots: Vec<Component>,
+    pub source: Source,
+}
+
+/// The SPDX ID of the document itself.
+const SPDX_DOCUMENT: &str = "SPDXRef-DOCUMENT";
+
+/// An SBOM, together with the file it came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFile {
+    pub name: String,
+    pub raw: Rc<String>,
+    pub sbom: Rc<Sbom>,
+}
+
+/// Map all (normalized) package URLs to the names of the files they can be found in.
+pub fn sources(files: &[SourceFile]) -> BTreeMap<String, Vec<String>> {
+    let mut result = BTreeMap::<String, Vec<String>>::new();
+    for file in files {
+        for purl in file.sbom.purls().map(normalize).collect::<BTreeSet<_>>() {
+            result.entry(purl).or_default().push(file.name.clone());
+        }
+    }
+    result
+}
+
+/// Map all normalized package URLs to the ones used in the files.
+pub fn originals(files: &[SourceFile]) -> BTreeMap<String, Vec<String>> {
+    let mut result = BTreeMap::<String, BTreeSet<String>>::new();
+    for purl in files.iter().flat_map(|file| file.sbom.purls()) {
+        result
+            .entry(normalize(purl))
+            .or_default()
+            .insert(purl.to_string());
+    }
+    result
+        .into_iter()
+        .map(|(purl, originals)| (purl, originals.into_iter().collect()))
+        .collect()
+}
+
+/// The original document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    CycloneDx(Bom),
+    Spdx(spdx::Document),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Component {
+    /// The document local reference, the `bom-ref` or `SPDXID`
+    pub id: Option<String>,
+    pub name: String,
+    pub version: Option<String>,
+    pub purl: Option<String>,
+    /// The type of component, like `library` or `application`
+    pub r#type: Option<String>,
+    pub group: Option<String>,
+    pub cpe: Option<String>,
+    pub hashes: Vec<Hash>,
+    pub licenses: Vec<License>,
+    /// The names of the components this component is nested in, outermost first
+    pub parents: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hash {
+    pub algorithm: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum License {
+    /// An SPDX license identifier or expression
+    Spdx(String),
+    /// The name of a license, which isn't an SPDX identifier
+    Name(String),
+}
+
+impl Component {
+    /// The name, prefixed with the names of the parents.
+    pub fn full_name(&self) -> String {
+        self.parents
+            .iter()
+            .chain(Some(&self.name))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+impl From<&cyclonedx_bom::models::component::Component> for Component {
+    fn from(c: &cyclonedx_bom::models::component::Component) -> Self {
+        Self {
+            id: c.bom_ref.clone(),
+            name: c.name.to_string(),
+            version: c.version.as_ref().map(ToString::to_string),
+            purl: c.purl.as_ref().map(ToString::to_string),
+            r#type: Some(c.component_type.to_string()),
+            group: c.group.as_ref().map(ToString::to_string),
+            cpe: c.cpe.as_ref().map(ToString::to_string),
+            hashes: c
+                .hashes
+                .as_ref()
+                .map(|hashes| {
+                    hashes
+                        .0
+                        .iter()
+                        .map(|h| Hash {
+                            algorithm: h.alg.to_string(),
+                            value: h.content.0.clone(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            licenses: c
+                .licenses
+                .as_ref()
+                .map(|licenses| {
+                    licenses
+                        .0
+                        .iter()
+                        .map(|license| match license {
+                            LicenseChoice::Expression(expression) => {
+                                License::Spdx(expression.to_string())
+                            }
+                            LicenseChoice::License(license) => match &license.license_identifier {
+                                LicenseIdentifier::SpdxId(id) => License::Spdx(id.to_string()),
+                                LicenseIdentifier::Name(name) => License::Name(name.to_string()),
+                            },
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            parents: vec![],
+        }
+    }
+}
+
+/// Flatten a hierarchy of CycloneDX components, recording the parents of each component.
+fn flatten(
+    components: &[cyclonedx_bom::models::component::Component],
+    parents: &[String],
+    result: &mut Vec<Component>,
+) {
+    for c in components {
+        result.push(Component {
+            parents: parents.to_vec(),
+            ..Component::from(c)
+        });
+
+        if let Some(children) = &c.components {
+            let mut parents = parents.to_vec();
+            parents.push(c.name.to_string());
+            flatten(&children.0, &parents, result);
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relationship {
+    pub from: String,
+    pub to: String,
+    pub r#type: String,
+}
+
+impl Sbom {
+    pub fn from_cyclonedx(r#type: DocumentType, spec_version: SpecVersion, bom: Bom) -> Self {
+        // the described component, as well as nested components, need to be checked too
+        let mut components = vec![];
+        if let Some(component) = bom.metadata.as_ref().and_then(|m| m.component.as_ref()) {
+            flatten(std::slice::from_ref(component), &[], &mut components);
+        }
+        if let Some(c) = &bom.components {
+            flatten(&c.0, &[], &mut components);
+        }
+
+        let relationships = bom
+            .dependencies
+            .as_ref()
+            .map(|deps| {
+                deps.0
+                    .iter()
+                    .flat_map(|dep| {
+                        dep.dependencies.iter().map(|to| Relationship {
+                            from: dep.dependency_ref.clone(),
+                            to: to.clone(),
+                            r#type: "DEPENDS_ON".to_string(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let roots = bom
+            .metadata
+            .as_ref()
+            .and_then(|m| m.component.as_ref())
+            .map(Component::from)
+            .into_iter()
+            .collect();
+
+        Self {
+            r#type,
+            spec_version: spec_version.to_string(),
+            components,
+            relationships,
+            roots,
+            source: Source::CycloneDx(bom),
+        }
+    }
+
+    pub fn from_spdx(r#type: DocumentType, doc: spdx::Document) -> Self {
+        let components: Vec<Component> = doc
+            .packages
+            .iter()
+            .map(|p| Component {
+                id: Some(p.spdx_id.clone()),
+                name: p.name.clone(),
+                version: p.version_info.clone(),
+                purl: p.purl().map(ToString::to_string),
+                r#type: None,
+                group: None,
+                cpe: p.cpe().map(ToString::to_string),
+                hashes: p
+                    .checksums
+                    .iter()
+                    .map(|c| Hash {
+                        algorithm: c.algorithm.clone(),
+                        value: c.checksum_value.clone(),
+                    })
+                    .collect(),
+                licenses: p
+                    .license()
+                    .map(|license| License::Spdx(license.to_string()))
+                    .into_iter()
+                    .collect(),
+                parents: vec![],
+            })
+            .collect();
+
+        let relationships = doc
+            .relationships
+            .iter()
+            .map(|r| Relationship {
+                from: r.spdx_element_id.clone(),
+                to: r.related_spdx_element.clone(),
+                r#type: r.relationship_type.clone(),
+            })
+            .collect();
+
+        let spec_version = doc
+            .spdx_version
+            .strip_prefix("SPDX-")
+            .unwrap_or(&doc.spdx_version)
+            .to_string();
+
+        // the document describes its root packages
+        let roots = doc
+            .relationships
+            .iter()
+            .filter(|r| r.relationship_type == "DESCRIBES" && r.spdx_element_id == SPDX_DOCUMENT)
+            .filter_map(|r| {
+                components
+                    .iter()
+                    .find(|c: &&Component| c.id.as_deref() == Some(&r.related_spdx_element))
+                    .cloned()
+            })
+            .collect();
+
+        Self {
+            r#type,
+            spec_version,
+            components,
+            relationships,
+            roots,
+            source: Source::Spdx(doc),
+        }
+    }
+
+    /// Get the direct dependencies of all elements, by their document local reference.
+    pub fn dependencies(&self) -> HashMap<&str, Vec<&str>> {
+        let mut result = HashMap::<&str, Vec<&str>>::new();
+        for r in &self.relationships {
+            let (from, to) = match r.r#type.as_str() {
+                "DEPENDS_ON" | "CONTAINS" | "DYNAMIC_LINK" | "STATIC_LINK" => {
+                    (r.from.as_str(), r.to.as_str())
+                }
+                "DEPENDENCY_OF"
+                | "CONTAINED_BY"
+                | "RUNTIME_DEPENDENCY_OF"
+                | "BUILD_DEPENDENCY_OF" => (r.to.as_str(), r.from.as_str()),
+                _ => continue,
+            };
+            result.entry(from).or_default().push(to);
+        }
+        result
+    }
+
+    /// Get the package URLs of all components which have one.
+    pub fn purls(&self) -> impl Iterator<Item = &str> {
+        self.components.iter().filter_map(|c| c.purl.as_deref())
+    }
+}
@@ -0,0 +1,200 @@
+use super::{downgrade, model::Sbom, spdx};
+use cyclonedx_bom::{
+    errors::{JsonReadError, XmlReadError},
+    prelude::Bom,
+};
+use std::str::FromStr;
+
+/// The SBOM standard of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display)]
+pub enum Kind {
+    #[strum(to_string = "CycloneDX")]
+    CycloneDx,
+    #[strum(to_string = "SPDX")]
+    Spdx,
+}
+
+/// The serialization format of an SBOM document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display)]
+pub enum Format {
+    #[strum(to_string = "JSON")]
+    Json,
+    #[strum(to_string = "XML")]
+    Xml,
+    #[strum(to_string = "tag-value")]
+    TagValue,
+}
+
+impl Format {
+    /// Detect the format from the content, by looking at the first non-whitespace character.
+    pub fn detect(data: &str) -> Option<Self> {
+        match data.trim_start().chars().next() {
+            Some('{') => Some(Self::Json),
+            Some('<') => Some(Self::Xml),
+            _ if spdx::is_tag_value(data) => Some(Self::TagValue),
+            _ => None,
+        }
+    }
+}
+
+/// The CycloneDX specification versions we can read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum::Display, strum::EnumString)]
+pub enum SpecVersion {
+    #[strum(serialize = "1.3")]
+    V1_3,
+    #[strum(serialize = "1.4")]
+    V1_4,
+    #[strum(serialize = "1.5")]
+    V1_5,
+}
+
+const CYCLONEDX_XML_NAMESPACE: &str = "http://cyclonedx.org/schema/bom/";
+
+/// The detected type of a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DocumentType {
+    pub kind: Kind,
+    pub format: Format,
+}
+
+impl std::fmt::Display for DocumentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.kind, self.format)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Json(#[from] JsonReadError),
+    #[error(transparent)]
+    JsonSyntax(#[from] serde_json::Error),
+    #[error(transparent)]
+    Xml(#[from] XmlReadError),
+    #[error(transparent)]
+    XmlSyntax(#[from] roxmltree::Error),
+    #[error("expected a CycloneDX 'bom' root element, found '{0}'")]
+    XmlRoot(String),
+    #[error("missing CycloneDX specification version")]
+    MissingSpecVersion,
+    #[error("unsupported CycloneDX specification version '{0}', supported are 1.3, 1.4 and 1.5")]
+    UnsupportedSpecVersion(String),
+    #[error(transparent)]
+    UnsupportedContent(#[from] downgrade::UnsupportedContent),
+    #[error(transparent)]
+    TagValue(#[from] spdx::TagValueError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseError {
+    #[error("Unable to detect the SBOM format, expected CycloneDX (JSON, XML) or SPDX (JSON, tag-value)")]
+    UnknownFormat,
+    #[error("Failed to parse SBOM (detected format: {0}): {1}")]
+    Parse(String, #[source] Error),
+}
+
+/// Parse an SBOM, detecting the format from the content.
+pub fn parse(data: &str) -> Result<Sbom, ParseError> {
+    match Format::detect(data) {
+        Some(Format::Json) => parse_json(data),
+        Some(Format::Xml) => parse_xml(data),
+        Some(Format::TagValue) => parse_tag_value(data),
+        None => Err(ParseError::UnknownFormat),
+    }
+}
+
+/// Run the parser, attaching the document type to any error.
+fn with_type<F>(r#type: DocumentType, f: F) -> Result<Sbom, ParseError>
+where
+    F: FnOnce() -> Result<Sbom, Error>,
+{
+    f().map_err(|err| ParseError::Parse(r#type.to_string(), err))
+}
+
+fn parse_json(data: &str) -> Result<Sbom, ParseError> {
+    let mut value: serde_json::Value = serde_json::from_str(data)
+        .map_err(|err| ParseError::Parse(Format::Json.to_string(), err.into()))?;
+
+    if value.get("spdxVersion").is_some() {
+        let r#type = DocumentType {
+            kind: Kind::Spdx,
+            format: Format::Json,
+        };
+        with_type(r#type, || {
+            Ok(Sbom::from_spdx(r#type, serde_json::from_value(value)?))
+        })
+    } else {
+        let r#type = DocumentType {
+            kind: Kind::CycloneDx,
+            format: Format::Json,
+        };
+        with_type(r#type, || {
+            let version = spec_version(value.get("specVersion").and_then(|v| v.as_str()))?;
+            let bom = match version {
+                SpecVersion::V1_3 => Bom::parse_from_json_v1_3(data.as_bytes())?,
+                SpecVersion::V1_4 => Bom::parse_from_json_v1_4(data.as_bytes())?,
+                SpecVersion::V1_5 => {
+                    // cyclonedx-bom can only read up to 1.4, so we read 1.5 documents as 1.4,
+                    // after removing what 1.5 added
+                    downgrade::json(&mut value)?;
+                    Bom::parse_from_json_v1_4(serde_json::to_vec(&value)?.as_slice())?
+                }
+            };
+            Ok(Sbom::from_cyclonedx(r#type, version, bom))
+        })
+    }
+}
+
+fn parse_xml(data: &str) -> Result<Sbom, ParseError> {
+    let r#type = DocumentType {
+        kind: Kind::CycloneDx,
+        format: Format::Xml,
+    };
+
+    with_type(r#type, || {
+        // check the document with roxmltree first, it gives us much better error messages
+        // (including the position) than the XML reader of cyclonedx-bom
+        let doc = roxmltree::Document::parse(data)?;
+        let root = doc.root_element();
+        if root.tag_name().name() != "bom" {
+            return Err(Error::XmlRoot(root.tag_name().name().to_string()));
+        }
+
+        // the specification version is part of the namespace
+        let version = spec_version(
+            root.tag_name()
+                .namespace()
+                .and_then(|ns| ns.strip_prefix(CYCLONEDX_XML_NAMESPACE)),
+        )?;
+        let bom = match version {
+            SpecVersion::V1_3 => Bom::parse_from_xml_v1_3(data.as_bytes())?,
+            SpecVersion::V1_4 => Bom::parse_from_xml_v1_4(data.as_bytes())?,
+            SpecVersion::V1_5 => {
+                // same as for JSON, read as 1.4
+                let data = downgrade::xml(data, &doc)?.replace(
+                    &format!("{CYCLONEDX_XML_NAMESPACE}{}", SpecVersion::V1_5),
+                    &format!("{CYCLONEDX_XML_NAMESPACE}{}", SpecVersion::V1_4),
+                );
+                Bom::parse_from_xml_v1_4(data.as_bytes())?
+            }
+        };
+
+        Ok(Sbom::from_cyclonedx(r#type, version, bom))
+    })
+}
+
+fn spec_version(version: Option<&str>) -> Result<SpecVersion, Error> {
+    let version = version.ok_or(Error::MissingSpecVersion)?;
+    SpecVersion::from_str(version).map_err(|_| Error::UnsupportedSpecVersion(version.to_string()))
+}
+
+fn parse_tag_value(data: &str) -> Result<Sbom, ParseError> {
+    let r#type = DocumentType {
+        kind: Kind::Spdx,
+        format: Format::TagValue,
+    };
+
+    with_type(r#type, || {
+        Ok(Sbom::from_spdx(r#type, spdx::parse_tag_value(data)?))
+    })
+}
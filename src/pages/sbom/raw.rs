@@ -0,0 +1,527 @@
+//! A structured, navigable view on the raw SBOM document.
+//!
+//! Large documents can contain tens of thousands of nodes, so only the children of expanded
+//! nodes are rendered, and long lists of children only in parts.
+
+use super::{parse::Format, spdx};
+use crate::{components::highlight, purl::normalize};
+use patternfly_yew::prelude::*;
+use std::collections::HashSet;
+use std::rc::Rc;
+use yew::prelude::*;
+
+/// The number of children of a node which are rendered, unless the user asks for all.
+const MAX_CHILDREN: usize = 100;
+
+/// The maximum number of search matches.
+const MAX_MATCHES: usize = 1000;
+
+/// The position of a node in the tree, the indices of the node and its parents.
+type Path = Vec<usize>;
+
+/// A node of the document, independent of the format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    /// The key, index or element name
+    label: String,
+    /// The value of a leaf, or the text of an element
+    value: Option<String>,
+    /// The package URL of the component this node describes
+    purl: Option<String>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn leaf(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: Some(value.into()),
+            purl: None,
+            children: vec![],
+        }
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        self.label.to_ascii_lowercase().contains(query)
+            || self
+                .value
+                .as_ref()
+                .map(|value| value.to_ascii_lowercase().contains(query))
+                .unwrap_or_default()
+    }
+}
+
+fn from_json(label: String, value: &serde_json::Value) -> Node {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(members) => Node {
+            label,
+            value: None,
+            purl: json_purl(members),
+            children: members
+                .iter()
+                .map(|(key, value)| from_json(key.clone(), value))
+                .collect(),
+        },
+        Value::Array(items) => Node {
+            label,
+            value: None,
+            purl: None,
+            children: items
+                .iter()
+                .enumerate()
+                .map(|(n, value)| from_json(format!("[{n}]"), value))
+                .collect(),
+        },
+        Value::String(value) => Node::leaf(label, value.as_str()),
+        value => Node::leaf(label, value.to_string()),
+    }
+}
+
+/// The package URL of a CycloneDX component or SPDX package.
+fn json_purl(members: &serde_json::Map<String, serde_json::Value>) -> Option<String> {
+    if let Some(purl) = members.get("purl").and_then(|purl| purl.as_str()) {
+        return Some(purl.to_string());
+    }
+
+    members
+        .get("externalRefs")?
+        .as_array()?
+        .iter()
+        .find(|r| r["referenceType"] == "purl")
+        .and_then(|r| r["referenceLocator"].as_str())
+        .map(ToString::to_string)
+}
+
+fn from_xml(element: roxmltree::Node) -> Node {
+    let elements = element
+        .children()
+        .filter(|child| child.is_element())
+        .map(from_xml)
+        .collect::<Vec<_>>();
+
+    // the text is the value of elements without child elements, even if they have attributes
+    let value = match elements.is_empty() {
+        true => element
+            .text()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty()),
+        false => None,
+    };
+
+    let mut children = element
+        .attributes()
+        .map(|attr| Node::leaf(format!("@{}", attr.name()), attr.value()))
+        .collect::<Vec<_>>();
+    children.extend(elements);
+
+    let purl = element
+        .children()
+        .find(|child| child.has_tag_name("purl"))
+        .and_then(|purl| purl.text())
+        .map(|purl| purl.trim().to_string());
+
+    Node {
+        label: element.tag_name().name().to_string(),
+        value,
+        purl,
+        children,
+    }
+}
+
+/// Read an SPDX tag-value document, grouping the tags of a package below its name.
+fn from_tag_value(data: &str) -> Result<Vec<Node>, spdx::TagValueError> {
+    let mut result = vec![];
+    let mut package: Option<Node> = None;
+
+    for tag_value in spdx::tag_values(data) {
+        let spdx::TagValue { tag, value, .. } = tag_value?;
+
+        match tag.as_str() {
+            "PackageName" => {
+                result.extend(package.take());
+                package = Some(Node::leaf(tag, value));
+            }
+            "FileName" | "SnippetSPDXID" | "LicenseID" => {
+                result.extend(package.take());
+                result.push(Node::leaf(tag, value));
+            }
+            _ => match &mut package {
+                Some(package) => {
+                    if tag == "ExternalRef" {
+                        let mut s = value.split_whitespace();
+                        if let (Some(_), Some("purl"), Some(locator)) =
+                            (s.next(), s.next(), s.next())
+                        {
+                            package.purl = Some(locator.to_string());
+                        }
+                    }
+                    package.children.push(Node::leaf(tag, value));
+                }
+                None => result.push(Node::leaf(tag, value)),
+            },
+        }
+    }
+
+    result.extend(package);
+    Ok(result)
+}
+
+/// Parse the raw document into the nodes of the top level.
+fn parse(raw: &str, format: Format) -> Result<Vec<Node>, String> {
+    match format {
+        Format::Json => {
+            let value =
+                serde_json::from_str::<serde_json::Value>(raw).map_err(|err| err.to_string())?;
+            Ok(from_json(String::new(), &value).children)
+        }
+        Format::Xml => {
+            let doc = roxmltree::Document::parse(raw).map_err(|err| err.to_string())?;
+            Ok(vec![from_xml(doc.root_element())])
+        }
+        Format::TagValue => from_tag_value(raw).map_err(|err| err.to_string()),
+    }
+}
+
+/// Find the paths of all nodes matching the (lowercase) query.
+fn find(nodes: &[Node], query: &str, path: &mut Path, result: &mut Vec<Path>) {
+    for (n, node) in nodes.iter().enumerate() {
+        if result.len() >= MAX_MATCHES {
+            return;
+        }
+        path.push(n);
+        if node.matches(query) {
+            result.push(path.clone());
+        }
+        find(&node.children, query, path, result);
+        path.pop();
+    }
+}
+
+/// Find the path of the first node describing the (normalized) package URL.
+fn find_purl(nodes: &[Node], purl: &str) -> Option<Path> {
+    for (n, node) in nodes.iter().enumerate() {
+        if node.purl.as_deref().map(normalize).as_deref() == Some(purl) {
+            return Some(vec![n]);
+        }
+        if let Some(mut path) = find_purl(&node.children, purl) {
+            path.insert(0, n);
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Everything required for rendering the tree.
+struct RenderContext<'a> {
+    expanded: &'a HashSet<Path>,
+    show_all: &'a HashSet<Path>,
+    selected: Option<&'a Path>,
+    selected_ref: &'a NodeRef,
+    query: &'a str,
+    ontoggle: &'a Callback<Path>,
+    onshowall: &'a Callback<Path>,
+}
+
+fn render_nodes(nodes: &[Node], path: &mut Path, ctx: &RenderContext) -> Html {
+    let limit = match ctx.show_all.contains(&path[..]) {
+        true => nodes.len(),
+        false => MAX_CHILDREN,
+    };
+
+    let mut result = vec![];
+    for (n, node) in nodes.iter().take(limit).enumerate() {
+        path.push(n);
+        result.push(render_node(node, path, ctx));
+        path.pop();
+    }
+
+    if nodes.len() > limit {
+        let onclick = ctx.onshowall.reform({
+            let path = path.clone();
+            move |_| path.clone()
+        });
+        result.push(html!(
+            <div style={format!("padding-left: {}em;", path.len() * 2)}>
+                <Button
+                    variant={ButtonVariant::Link}
+                    label={format!("Show {} more", nodes.len() - limit)}
+                    {onclick}
+                />
+            </div>
+        ));
+    }
+
+    html!({ for result })
+}
+
+fn render_node(node: &Node, path: &mut Path, ctx: &RenderContext) -> Html {
+    let expanded = ctx.expanded.contains(&path[..]);
+    let selected = ctx.selected.map(|s| s[..] == path[..]).unwrap_or_default();
+
+    let toggle = match node.children.is_empty() {
+        true => html!(),
+        false => {
+            let onclick = ctx.ontoggle.reform({
+                let path = path.clone();
+                move |_| path.clone()
+            });
+            let icon = match expanded {
+                true => Icon::AngleDown,
+                false => Icon::AngleRight,
+            };
+            html!(<Button variant={ButtonVariant::Plain} {icon} {onclick} />)
+        }
+    };
+
+    let mut class = classes!("pf-u-font-family-monospace");
+    if selected {
+        class.push("pf-u-background-color-200");
+    }
+    let node_ref = match selected {
+        true => ctx.selected_ref.clone(),
+        false => NodeRef::default(),
+    };
+
+    html!(
+        <>
+            <div {class} ref={node_ref} style={format!("padding-left: {}em;", (path.len() - 1) * 2)}>
+                { toggle }
+                <strong>{ highlight(&node.label, ctx.query) }</strong>
+                if let Some(value) = &node.value {
+                    { ": " } { highlight(value, ctx.query) }
+                } else if !expanded && !node.children.is_empty() {
+                    <span class="pf-u-color-200">{ format!(" ({})", node.children.len()) }</span>
+                }
+            </div>
+            if expanded {
+                { render_nodes(&node.children, path, ctx) }
+            }
+        </>
+    )
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct RawDocumentProperties {
+    pub raw: Rc<String>,
+    pub format: Format,
+    /// The package URL of the component to show
+    #[prop_or_default]
+    pub target: Option<String>,
+}
+
+#[function_component(RawDocument)]
+pub fn raw_document(props: &RawDocumentProperties) -> Html {
+    let nodes = use_memo(
+        |(raw, format)| parse(raw, *format),
+        (props.raw.clone(), props.format),
+    );
+
+    let expanded = use_state_eq(HashSet::<Path>::new);
+    let show_all = use_state_eq(HashSet::<Path>::new);
+    let selected = use_state_eq(|| None::<Path>);
+    let selected_ref = use_node_ref();
+
+    let query = use_state_eq(String::new);
+    let input = use_state_eq(String::new);
+    let current = use_state_eq(|| 0usize);
+
+    let matches = use_memo(
+        |(query, nodes)| {
+            let mut result = vec![];
+            if let (false, Ok(nodes)) = (query.is_empty(), &**nodes) {
+                find(nodes, query, &mut vec![], &mut result);
+            }
+            result
+        },
+        ((*query).clone(), nodes.clone()),
+    );
+
+    // expand all parents of a node, and select it
+    let select = {
+        let expanded = expanded.clone();
+        let show_all = show_all.clone();
+        let selected = selected.clone();
+        Callback::from(move |path: Path| {
+            let mut e = (*expanded).clone();
+            let mut s = (*show_all).clone();
+            for n in 0..path.len() {
+                e.insert(path[..n].to_vec());
+                s.insert(path[..n].to_vec());
+            }
+            expanded.set(e);
+            show_all.set(s);
+            selected.set(Some(path));
+        })
+    };
+
+    {
+        // jump to the target component
+        let select = select.clone();
+        use_effect_with_deps(
+            move |(nodes, target)| {
+                if let (Ok(nodes), Some(target)) = (&**nodes, target) {
+                    if let Some(path) = find_purl(nodes, &normalize(target)) {
+                        select.emit(path);
+                    }
+                }
+            },
+            (nodes.clone(), props.target.clone()),
+        );
+    }
+
+    {
+        // jump to the current match
+        let select = select.clone();
+        use_effect_with_deps(
+            move |(matches, current)| {
+                if let Some(path) = matches.get(*current) {
+                    select.emit(path.clone());
+                }
+            },
+            (matches.clone(), *current),
+        );
+    }
+
+    {
+        let selected_ref = selected_ref.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(element) = selected_ref.cast::<web_sys::Element>() {
+                    element.scroll_into_view();
+                }
+            },
+            (*selected).clone(),
+        );
+    }
+
+    let ontoggle = {
+        let expanded = expanded.clone();
+        Callback::from(move |path: Path| {
+            let mut e = (*expanded).clone();
+            if !e.remove(&path) {
+                e.insert(path);
+            }
+            expanded.set(e);
+        })
+    };
+
+    let onshowall = {
+        let show_all = show_all.clone();
+        Callback::from(move |path: Path| {
+            let mut s = (*show_all).clone();
+            s.insert(path);
+            show_all.set(s);
+        })
+    };
+
+    let oninput = {
+        let input = input.clone();
+        Callback::from(move |data: String| input.set(data))
+    };
+
+    let onset = {
+        let input = input.clone();
+        let query = query.clone();
+        let current = current.clone();
+        Callback::from(move |_| {
+            query.set(input.trim().to_ascii_lowercase());
+            current.set(0);
+        })
+    };
+
+    let onclear = {
+        let input = input.clone();
+        let query = query.clone();
+        Callback::from(move |_| {
+            input.set(String::new());
+            query.set(String::new());
+        })
+    };
+
+    let onprevious = {
+        let current = current.clone();
+        let len = matches.len();
+        Callback::from(move |_| {
+            if len > 0 {
+                current.set((*current + len - 1) % len);
+            }
+        })
+    };
+
+    let onnext = {
+        let current = current.clone();
+        let len = matches.len();
+        Callback::from(move |_| {
+            if len > 0 {
+                current.set((*current + 1) % len);
+            }
+        })
+    };
+
+    let nodes = match &*nodes {
+        Ok(nodes) => nodes,
+        Err(err) => {
+            return html!(
+                <Alert r#type={AlertType::Danger} inline=true title="Failed to read the document">
+                    { err }
+                </Alert>
+            )
+        }
+    };
+
+    let ctx = RenderContext {
+        expanded: &expanded,
+        show_all: &show_all,
+        selected: (*selected).as_ref(),
+        selected_ref: &selected_ref,
+        query: query.as_str(),
+        ontoggle: &ontoggle,
+        onshowall: &onshowall,
+    };
+
+    html!(
+        <>
+            <Toolbar>
+                <ToolbarContent>
+                    <ToolbarItem r#type={ToolbarItemType::SearchFilter} width={["600px".to_string()]}>
+                        <TextInputGroup>
+                            <TextInputGroupMain
+                                icon={Icon::Search}
+                                placeholder="Search the document"
+                                value={(*input).clone()}
+                                {oninput}
+                            />
+                            if !input.is_empty() {
+                                <TextInputGroupUtilities>
+                                    <Button icon={Icon::Times} variant={ButtonVariant::Plain} onclick={onclear} />
+                                </TextInputGroupUtilities>
+                            }
+                            <Button icon={Icon::ArrowRight} variant={ButtonVariant::Control} onclick={onset} />
+                        </TextInputGroup>
+                    </ToolbarItem>
+                    if !query.is_empty() {
+                        <ToolbarItem>
+                            {
+                                match matches.len() {
+                                    0 => "No matches".to_string(),
+                                    len @ MAX_MATCHES => format!("{} of {len}+", *current + 1),
+                                    len => format!("{} of {len}", *current + 1),
+                                }
+                            }
+                        </ToolbarItem>
+                        <ToolbarItem>
+                            <Button variant={ButtonVariant::Secondary} label="Previous" onclick={onprevious} disabled={matches.is_empty()} />
+                        </ToolbarItem>
+                        <ToolbarItem>
+                            <Button variant={ButtonVariant::Secondary} label="Next" onclick={onnext} disabled={matches.is_empty()} />
+                        </ToolbarItem>
+                    }
+                </ToolbarContent>
+            </Toolbar>
+
+            { render_nodes(nodes, &mut vec![], &ctx) }
+        </>
+    )
+}
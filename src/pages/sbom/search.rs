@@ -0,0 +1,391 @@
+//! Searching for large numbers of packages, split into chunks.
+
+use crate::{
+    backend::{self, data::PackageDependents, PackageService},
+    hooks::use_backend,
+};
+use packageurl::PackageUrl;
+use patternfly_yew::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::str::FromStr;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+
+/// A request for package URLs, which can be split into chunks.
+pub trait Request: Clone + Default + PartialEq + 'static {
+    type Item: Clone + PartialEq + 'static;
+    type Output: Clone + PartialEq + 'static;
+
+    /// What the request does, shown with its progress.
+    const DESCRIPTION: &'static str;
+
+    fn send(
+        service: Rc<PackageService>,
+        purls: Vec<PackageUrl<'static>>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Self::Item>, backend::Error>>>>;
+
+    /// Merge the items of all chunks.
+    fn merge(items: Vec<Self::Item>) -> Self::Output;
+}
+
+/// Searching the references of packages.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Search;
+
+impl Request for Search {
+    type Item = PackageRef;
+    type Output = PackageDependents;
+
+    const DESCRIPTION: &'static str = "Searching packages";
+
+    fn send(
+        service: Rc<PackageService>,
+        purls: Vec<PackageUrl<'static>>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Self::Item>, backend::Error>>>> {
+        Box::pin(async move { service.search(purls).await.map(|refs| refs.0) })
+    }
+
+    fn merge(items: Vec<Self::Item>) -> Self::Output {
+        PackageDependents(items)
+    }
+}
+
+/// Looking up the full information of packages.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Lookup;
+
+impl Request for Lookup {
+    type Item = Package;
+    type Output = Vec<Package>;
+
+    const DESCRIPTION: &'static str = "Loading package details";
+
+    fn send(
+        service: Rc<PackageService>,
+        purls: Vec<PackageUrl<'static>>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Self::Item>, backend::Error>>>> {
+        Box::pin(async move { service.lookup_packages(purls).await })
+    }
+
+    fn merge(items: Vec<Self::Item>) -> Self::Output {
+        items
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Chunk<T> {
+    Pending,
+    Done(Vec<T>),
+    Failed(String),
+}
+
+#[derive(Clone)]
+struct SearchState<R: Request> {
+    /// Incremented with every new search, to drop results of outdated searches
+    generation: usize,
+    chunks: Vec<Chunk<R::Item>>,
+    /// The merged result, once all chunks succeeded
+    result: Option<R::Output>,
+}
+
+impl<R: Request> Default for SearchState<R> {
+    fn default() -> Self {
+        Self {
+            generation: 0,
+            chunks: vec![],
+            result: None,
+        }
+    }
+}
+
+enum SearchAction<T> {
+    Start {
+        generation: usize,
+        chunks: usize,
+    },
+    Update {
+        generation: usize,
+        index: usize,
+        chunk: Chunk<T>,
+    },
+    /// Nothing to search, for now
+    Reset,
+}
+
+impl<R: Request> Reducible for SearchState<R> {
+    type Action = SearchAction<R::Item>;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut state = (*self).clone();
+
+        match action {
+            SearchAction::Start { generation, chunks } => {
+                state.generation = generation;
+                state.chunks = vec![Chunk::Pending; chunks];
+            }
+            SearchAction::Update {
+                generation,
+                index,
+                chunk,
+            } => {
+                if generation != state.generation || index >= state.chunks.len() {
+                    return self;
+                }
+                state.chunks[index] = chunk;
+            }
+            SearchAction::Reset => {
+                state.generation += 1;
+                state.chunks.clear();
+                state.result = None;
+                return Rc::new(state);
+            }
+        }
+
+        state.result = state
+            .chunks
+            .iter()
+            .map(|chunk| match chunk {
+                Chunk::Done(items) => Some(items.iter().cloned()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|items| R::merge(items.into_iter().flatten().collect()));
+
+        Rc::new(state)
+    }
+}
+
+/// Run the search for the chunks with the provided indexes, limiting the number of parallel
+/// requests.
+fn run<R: Request>(
+    service: Rc<PackageService>,
+    chunks: Rc<Vec<Vec<String>>>,
+    indexes: Vec<usize>,
+    concurrency: usize,
+    generation: usize,
+    dispatcher: UseReducerDispatcher<SearchState<R>>,
+) {
+    let queue = Rc::new(RefCell::new(VecDeque::from(indexes)));
+
+    for _ in 0..concurrency.max(1) {
+        let service = service.clone();
+        let chunks = chunks.clone();
+        let queue = queue.clone();
+        let dispatcher = dispatcher.clone();
+
+        wasm_bindgen_futures::spawn_local(async move {
+            loop {
+                // don't hold on to the borrow while awaiting the request
+                let index = queue.borrow_mut().pop_front();
+                let index = match index {
+                    Some(index) => index,
+                    None => break,
+                };
+
+                let purls = chunks[index]
+                    .iter()
+                    .filter_map(|purl| PackageUrl::from_str(purl).ok())
+                    .collect();
+                let chunk = match R::send(service.clone(), purls).await {
+                    Ok(items) => Chunk::Done(items),
+                    Err(err) => Chunk::Failed(err.to_string()),
+                };
+
+                dispatcher.dispatch(SearchAction::Update {
+                    generation,
+                    index,
+                    chunk,
+                });
+            }
+        });
+    }
+}
+
+#[derive(Clone)]
+pub struct UseChunkedSearch<R: Request = Search> {
+    state: UseReducerHandle<SearchState<R>>,
+    retry: Rc<dyn Fn(Vec<usize>)>,
+}
+
+impl<R: Request> UseChunkedSearch<R> {
+    /// The merged result, once all chunks succeeded.
+    pub fn data(&self) -> Option<&R::Output> {
+        self.state.result.as_ref()
+    }
+
+    /// The indexes and errors of all failed chunks.
+    fn failed(&self) -> Vec<(usize, &str)> {
+        self.state
+            .chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| match chunk {
+                Chunk::Failed(err) => Some((index, err.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Run the failed chunks again, keeping the results of all others.
+    fn retry_failed(&self) {
+        (self.retry)(self.failed().into_iter().map(|(index, _)| index).collect());
+    }
+}
+
+/// Search for the package URLs, in chunks, as configured by the backend.
+#[hook]
+pub fn use_chunked_search(purls: Rc<Vec<String>>) -> UseChunkedSearch {
+    use_chunked::<Search>(Some(purls))
+}
+
+/// Lookup the full information of the package URLs, in chunks, once they are known.
+#[hook]
+pub fn use_chunked_lookup(purls: Option<Rc<Vec<String>>>) -> UseChunkedSearch<Lookup> {
+    use_chunked::<Lookup>(purls)
+}
+
+/// Run the request for the package URLs, in chunks, as configured by the backend. Nothing is
+/// requested while the package URLs are not known.
+#[hook]
+fn use_chunked<R: Request>(purls: Option<Rc<Vec<String>>>) -> UseChunkedSearch<R> {
+    let backend = use_backend();
+    let state = use_reducer(SearchState::<R>::default);
+    let generation = use_mut_ref(|| 0usize);
+
+    let service = use_memo(
+        |backend| PackageService::new((**backend).clone()),
+        backend.clone(),
+    );
+
+    let chunks = use_memo(
+        |(purls, chunk_size)| {
+            purls.as_ref().map(|purls| {
+                Rc::new(
+                    purls
+                        .chunks((*chunk_size).max(1))
+                        .map(|chunk| chunk.to_vec())
+                        .collect::<Vec<_>>(),
+                )
+            })
+        },
+        (purls, backend.search.chunk_size),
+    );
+
+    let concurrency = backend.search.concurrency;
+
+    {
+        let dispatcher = state.dispatcher();
+        let generation = generation.clone();
+        let service = service.clone();
+        use_effect_with_deps(
+            move |(chunks, _)| {
+                let chunks = match &**chunks {
+                    Some(chunks) => chunks.clone(),
+                    None => {
+                        dispatcher.dispatch(SearchAction::Reset);
+                        return;
+                    }
+                };
+
+                let current = {
+                    let mut generation = generation.borrow_mut();
+                    *generation += 1;
+                    *generation
+                };
+
+                dispatcher.dispatch(SearchAction::Start {
+                    generation: current,
+                    chunks: chunks.len(),
+                });
+                run(
+                    service,
+                    chunks.clone(),
+                    (0..chunks.len()).collect(),
+                    concurrency,
+                    current,
+                    dispatcher,
+                );
+            },
+            (chunks.clone(), backend.clone()),
+        );
+    }
+
+    let retry = {
+        let dispatcher = state.dispatcher();
+        Rc::new(move |indexes: Vec<usize>| {
+            let chunks = match &*chunks {
+                Some(chunks) => chunks.clone(),
+                None => return,
+            };
+            let current = *generation.borrow();
+            for index in &indexes {
+                dispatcher.dispatch(SearchAction::Update {
+                    generation: current,
+                    index: *index,
+                    chunk: Chunk::Pending,
+                });
+            }
+            run(
+                service.clone(),
+                chunks,
+                indexes,
+                concurrency,
+                current,
+                dispatcher.clone(),
+            );
+        })
+    };
+
+    UseChunkedSearch { state, retry }
+}
+
+/// Render the result of the search once it is complete, or its progress otherwise.
+pub fn search_content<R, F>(search: &UseChunkedSearch<R>, body: F) -> Html
+where
+    R: Request,
+    F: FnOnce(&R::Output) -> Html,
+{
+    if let Some(data) = search.data() {
+        return body(data);
+    }
+
+    let total = search.state.chunks.len();
+    let pending = search
+        .state
+        .chunks
+        .iter()
+        .filter(|chunk| matches!(chunk, Chunk::Pending))
+        .count();
+    let failed = search.failed();
+
+    let description = format!("{}: {} of {total} batches", R::DESCRIPTION, total - pending);
+    let value = match total {
+        0 => 0.0,
+        total => ((total - pending) * 100 / total) as f64,
+    };
+
+    let onretry = {
+        let search = search.clone();
+        Callback::from(move |_| search.retry_failed())
+    };
+
+    html!(
+        <PageSection fill={PageSectionFill::Fill}>
+            <Progress {description} {value} />
+            if !failed.is_empty() && pending == 0 {
+                <Alert r#type={AlertType::Danger} title={format!("{} of {total} batches failed", failed.len())} inline=true>
+                    <List>
+                        { for failed.iter().map(|(index, err)| html!(
+                            <>{ format!("Batch {}: {err}", index + 1) }</>
+                        )) }
+                    </List>
+                    <Button variant={ButtonVariant::Secondary} label="Retry failed" onclick={onretry} />
+                </Alert>
+            }
+        </PageSection>
+    )
+}
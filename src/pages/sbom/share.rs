@@ -0,0 +1,216 @@
+//! Sharing an SBOM inspection as a link.
+//!
+//! Small inspections are carried in the fragment of the link itself, compressed and encoded.
+//! Larger ones are stored by the backend, and the link carries the ID to load them again.
+
+use super::{history, inspect::Inspect, model::SourceFile, parse_files, upload::UploadedContent};
+use crate::{
+    backend::{self, SBOMService, SharedDocument},
+    hooks::use_backend,
+    pages::AppRoute,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use patternfly_yew::prelude::*;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew_more_hooks::hooks::r#async::*;
+use yew_nested_router::Target;
+
+/// The ID of inspections carried in the fragment of the link.
+const INLINE_ID: &str = "inline";
+
+/// The maximum length of the encoded documents in the fragment of a link.
+const MAX_FRAGMENT_LENGTH: usize = 16 * 1024;
+
+/// The maximum size of the decompressed documents of a link.
+const MAX_CONTENT_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ShareError {
+    #[error("Failed to encode documents: {0}")]
+    Encode(#[source] serde_json::Error),
+    #[error("Failed to decode documents: {0}")]
+    Decode(#[source] serde_json::Error),
+    #[error("The link is damaged: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("The link is damaged, unable to decompress its content")]
+    Decompress,
+    #[error("The link doesn't contain any documents")]
+    Empty,
+    #[error("Unable to get the location of the page: {0}")]
+    Location(String),
+    #[error(transparent)]
+    Backend(#[from] backend::Error),
+}
+
+/// Compress and encode the documents, for the fragment of a link.
+fn encode(documents: &[SharedDocument]) -> Result<String, ShareError> {
+    let json = serde_json::to_vec(documents).map_err(ShareError::Encode)?;
+    let compressed = miniz_oxide::deflate::compress_to_vec(&json, 9);
+    Ok(URL_SAFE_NO_PAD.encode(compressed))
+}
+
+fn decode(fragment: &str) -> Result<Vec<SharedDocument>, ShareError> {
+    let compressed = URL_SAFE_NO_PAD.decode(fragment)?;
+    let json = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_CONTENT_SIZE)
+        .map_err(|_| ShareError::Decompress)?;
+    serde_json::from_slice(&json).map_err(ShareError::Decode)
+}
+
+/// Create the link to an inspection.
+fn link(id: &str, fragment: Option<&str>) -> Result<String, ShareError> {
+    let origin = gloo_utils::window()
+        .location()
+        .origin()
+        .map_err(|err| ShareError::Location(format!("{err:?}")))?;
+    let path = AppRoute::SharedSBOM { id: id.to_string() }
+        .render_path()
+        .join("/");
+
+    Ok(match fragment {
+        Some(fragment) => format!("{origin}/{path}#{fragment}"),
+        None => format!("{origin}/{path}"),
+    })
+}
+
+/// Share the documents, returning the link.
+async fn share(files: &[SourceFile], backend: backend::Backend) -> Result<String, ShareError> {
+    let documents = files
+        .iter()
+        .map(|file| SharedDocument {
+            name: file.name.clone(),
+            content: (*file.raw).clone(),
+        })
+        .collect::<Vec<_>>();
+
+    let fragment = encode(&documents)?;
+    if fragment.len() <= MAX_FRAGMENT_LENGTH {
+        return link(INLINE_ID, Some(&fragment));
+    }
+
+    let id = SBOMService::new(backend).share(&documents).await?;
+    link(&id, None)
+}
+
+/// Load the documents of a shared inspection.
+async fn load(id: &str, backend: backend::Backend) -> Result<Vec<UploadedContent>, ShareError> {
+    let documents = match id {
+        INLINE_ID => {
+            let hash = gloo_utils::window()
+                .location()
+                .hash()
+                .map_err(|err| ShareError::Location(format!("{err:?}")))?;
+            decode(hash.trim_start_matches('#'))?
+        }
+        id => SBOMService::new(backend).shared(id).await?,
+    };
+
+    if documents.is_empty() {
+        return Err(ShareError::Empty);
+    }
+
+    Ok(documents
+        .into_iter()
+        .map(|document| UploadedContent {
+            name: document.name,
+            content: document.content,
+        })
+        .collect())
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct ShareProperties {
+    pub files: Rc<Vec<SourceFile>>,
+}
+
+/// Create a link to the inspection, on request.
+#[function_component(Share)]
+pub fn share_button(props: &ShareProperties) -> Html {
+    let backend = use_backend();
+
+    let sharing = {
+        let files = props.files.clone();
+        use_async_with_options(
+            async move {
+                share(&files, (*backend).clone())
+                    .await
+                    .map_err(|err| err.to_string())
+            },
+            UseAsyncOptions::default(),
+        )
+    };
+
+    let onclick = {
+        let sharing = sharing.clone();
+        Callback::from(move |_| sharing.run())
+    };
+
+    match &*sharing {
+        UseAsyncState::Pending => html!(
+            <Button variant={ButtonVariant::Link} icon={Icon::ExternalLinkAlt} label="Share" {onclick} />
+        ),
+        UseAsyncState::Processing => html!(<Spinner size={SpinnerSize::Md} />),
+        UseAsyncState::Ready(Ok(link)) => html!(
+            <Clipboard readonly=true value={link.clone()} />
+        ),
+        UseAsyncState::Ready(Err(err)) => html!(
+            <>
+                { format!("Failed to share: {err}") }
+                <Button variant={ButtonVariant::Link} label="Retry" {onclick} />
+            </>
+        ),
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct SharedSBOMProperties {
+    pub id: String,
+}
+
+/// Open a shared inspection.
+#[function_component(SharedSBOM)]
+pub fn shared_sbom(props: &SharedSBOMProperties) -> Html {
+    let backend = use_backend();
+
+    // opening a shared inspection creates a new entry in the history
+    let history_id = use_state(history::new_id);
+
+    let documents = use_async_with_cloned_deps(
+        |(id, backend)| async move {
+            load(&id, (*backend).clone())
+                .await
+                .map_err(|err| err.to_string())
+        },
+        (props.id.clone(), backend),
+    );
+
+    let files = use_memo(
+        |documents| documents.as_deref().and_then(parse_files),
+        documents.data().cloned(),
+    );
+
+    match (&*documents, &*files) {
+        (UseAsyncState::Ready(Err(err)), _) => html!(
+            <PageSection variant={PageSectionVariant::Light} fill=true>
+                <Alert r#type={AlertType::Danger} inline=true title="Failed to open the shared SBOM">
+                    { err }
+                </Alert>
+            </PageSection>
+        ),
+        (UseAsyncState::Ready(Ok(_)), None) => html!(
+            <PageSection variant={PageSectionVariant::Light} fill=true>
+                <Alert r#type={AlertType::Danger} inline=true title="Failed to open the shared SBOM">
+                    { "The shared documents are not valid SBOMs" }
+                </Alert>
+            </PageSection>
+        ),
+        (UseAsyncState::Ready(Ok(_)), Some(files)) => html!(
+            <Inspect id={(*history_id).clone()} files={files.clone()} />
+        ),
+        _ => html!(
+            <PageSection variant={PageSectionVariant::Light} fill=true>
+                <Spinner />
+            </PageSection>
+        ),
+    }
+}
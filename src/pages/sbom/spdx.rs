@@ -0,0 +1,270 @@
+//! Reading SPDX 2.x documents, in the JSON and tag-value format.
+//!
+//! We only read the parts we need for inspecting the document, which are the packages and the
+//! relationships between them.
+
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document {
+    pub spdx_version: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub packages: Vec<Package>,
+    #[serde(default)]
+    pub relationships: Vec<Relationship>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Package {
+    #[serde(rename = "SPDXID")]
+    pub spdx_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version_info: Option<String>,
+    #[serde(default)]
+    pub external_refs: Vec<ExternalRef>,
+    #[serde(default)]
+    pub checksums: Vec<Checksum>,
+    #[serde(default)]
+    pub license_concluded: Option<String>,
+    #[serde(default)]
+    pub license_declared: Option<String>,
+}
+
+impl Package {
+    /// Get the first package URL from the external references.
+    pub fn purl(&self) -> Option<&str> {
+        self.external_ref(&["purl"])
+    }
+
+    /// Get the first CPE from the external references.
+    pub fn cpe(&self) -> Option<&str> {
+        self.external_ref(&["cpe23Type", "cpe22Type"])
+    }
+
+    /// Get the license expression, preferring the concluded over the declared license.
+    pub fn license(&self) -> Option<&str> {
+        [&self.license_concluded, &self.license_declared]
+            .into_iter()
+            .flatten()
+            .map(|license| license.trim())
+            .find(|license| !license.is_empty() && !NO_LICENSE.contains(license))
+    }
+
+    fn external_ref(&self, types: &[&str]) -> Option<&str> {
+        self.external_refs
+            .iter()
+            .find(|r| types.contains(&r.reference_type.as_str()))
+            .map(|r| r.reference_locator.as_str())
+    }
+}
+
+/// Values which don't name a license.
+const NO_LICENSE: [&str; 2] = ["NOASSERTION", "NONE"];
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checksum {
+    pub algorithm: String,
+    pub checksum_value: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalRef {
+    pub reference_type: String,
+    pub reference_locator: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relationship {
+    pub spdx_element_id: String,
+    pub related_spdx_element: String,
+    pub relationship_type: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TagValueError {
+    #[error("line {line}: expected 'Tag: Value'")]
+    Syntax { line: usize },
+    #[error("line {line}: unterminated <text> value")]
+    UnterminatedText { line: usize },
+    #[error("line {line}: invalid value for '{tag}'")]
+    Value { line: usize, tag: String },
+    #[error("missing 'SPDXVersion' tag")]
+    MissingVersion,
+}
+
+/// Check if the content looks like an SPDX tag-value document.
+pub fn is_tag_value(data: &str) -> bool {
+    data.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.starts_with("SPDXVersion:"))
+        .unwrap_or_default()
+}
+
+/// A tag of a tag-value document, with its value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagValue {
+    /// The line the tag starts on, starting with 1
+    pub line: usize,
+    pub tag: String,
+    pub value: String,
+}
+
+/// Read the next tag, and its value, which may span multiple lines.
+fn next_tag_value<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> Option<Result<TagValue, TagValueError>> {
+    let (line, text) = lines
+        .by_ref()
+        .map(|(line, text)| (line, text.trim()))
+        .find(|(_, text)| !text.is_empty() && !text.starts_with('#'))?;
+
+    let (tag, value) = match text.split_once(':') {
+        Some(tag_value) => tag_value,
+        None => return Some(Err(TagValueError::Syntax { line })),
+    };
+    let mut value = value.trim().to_string();
+
+    if let Some(rest) = value.strip_prefix("<text>") {
+        // multi-line value, read until the closing tag
+        let mut text = rest.to_string();
+        while !text.contains("</text>") {
+            match lines.next() {
+                Some((_, next)) => {
+                    text.push('\n');
+                    text.push_str(next);
+                }
+                None => return Some(Err(TagValueError::UnterminatedText { line })),
+            }
+        }
+        value = text.replace("</text>", "").trim().to_string();
+    }
+
+    Some(Ok(TagValue {
+        line,
+        tag: tag.to_string(),
+        value,
+    }))
+}
+
+/// Read all tags of a tag-value document, in the order of the document.
+pub fn tag_values(data: &str) -> impl Iterator<Item = Result<TagValue, TagValueError>> + '_ {
+    let mut lines = data.lines().enumerate().map(|(n, line)| (n + 1, line));
+    std::iter::from_fn(move || next_tag_value(&mut lines))
+}
+
+/// Parse an SPDX document in the tag-value format.
+pub fn parse_tag_value(data: &str) -> Result<Document, TagValueError> {
+    let mut doc = Document::default();
+    let mut current: Option<Package> = None;
+
+    for tag_value in tag_values(data) {
+        let TagValue { line, tag, value } = tag_value?;
+
+        match tag.as_str() {
+            "SPDXVersion" => doc.spdx_version = value,
+            "DocumentName" => doc.name = Some(value),
+            "PackageName" => {
+                doc.packages.extend(current.take());
+                current = Some(Package {
+                    name: value,
+                    ..Default::default()
+                });
+            }
+            "SPDXID" => {
+                if let Some(package) = &mut current {
+                    package.spdx_id = value;
+                }
+            }
+            "PackageVersion" => {
+                if let Some(package) = &mut current {
+                    package.version_info = Some(value);
+                }
+            }
+            "ExternalRef" => {
+                if let Some(package) = &mut current {
+                    let mut s = value.split_whitespace();
+                    match (s.next(), s.next(), s.next()) {
+                        (Some(_category), Some(r#type), Some(locator)) => {
+                            package.external_refs.push(ExternalRef {
+                                reference_type: r#type.to_string(),
+                                reference_locator: locator.to_string(),
+                            })
+                        }
+                        _ => {
+                            return Err(TagValueError::Value {
+                                line,
+                                tag: tag.clone(),
+                            })
+                        }
+                    }
+                }
+            }
+            "PackageLicenseConcluded" => {
+                if let Some(package) = &mut current {
+                    package.license_concluded = Some(value);
+                }
+            }
+            "PackageLicenseDeclared" => {
+                if let Some(package) = &mut current {
+                    package.license_declared = Some(value);
+                }
+            }
+            "PackageChecksum" => {
+                if let Some(package) = &mut current {
+                    match value.split_once(':') {
+                        Some((algorithm, checksum)) => package.checksums.push(Checksum {
+                            algorithm: algorithm.trim().to_string(),
+                            checksum_value: checksum.trim().to_string(),
+                        }),
+                        None => {
+                            return Err(TagValueError::Value {
+                                line,
+                                tag: tag.clone(),
+                            })
+                        }
+                    }
+                }
+            }
+            "Relationship" => {
+                let mut s = value.split_whitespace();
+                match (s.next(), s.next(), s.next()) {
+                    (Some(element), Some(r#type), Some(related)) => {
+                        doc.relationships.push(Relationship {
+                            spdx_element_id: element.to_string(),
+                            related_spdx_element: related.to_string(),
+                            relationship_type: r#type.to_string(),
+                        })
+                    }
+                    _ => {
+                        return Err(TagValueError::Value {
+                            line,
+                            tag: tag.clone(),
+                        })
+                    }
+                }
+            }
+            "FileName" | "SnippetSPDXID" | "LicenseID" => {
+                // the package section ends with the next file, snippet or license section
+                doc.packages.extend(current.take());
+            }
+            _ => {}
+        }
+    }
+
+    doc.packages.extend(current);
+
+    if doc.spdx_version.is_empty() {
+        return Err(TagValueError::MissingVersion);
+    }
+
+    Ok(doc)
+}
@@ -0,0 +1,82 @@
+//! Suggesting trusted builds as replacements for found, but untrusted packages.
+
+use crate::version;
+use packageurl::PackageUrl;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::str::FromStr;
+use trust_api_model::prelude::*;
+
+/// The package, independent of its version and qualifiers.
+fn package_key(purl: &PackageUrl) -> (String, Option<String>, String) {
+    (
+        purl.ty().to_string(),
+        purl.namespace().map(ToString::to_string),
+        purl.name().to_string(),
+    )
+}
+
+/// The package URLs, without their versions, of all found packages which are not trusted.
+pub fn untrusted_packages(refs: &[PackageRef]) -> Vec<String> {
+    refs.iter()
+        .filter(|r| !r.trusted.unwrap_or_default())
+        .filter_map(|r| PackageUrl::from_str(&r.purl).ok())
+        .map(|mut purl| {
+            purl.without_version();
+            purl.to_string()
+        })
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Find the nearest trusted version for all found packages which are not trusted.
+///
+/// The nearest version is the lowest trusted version which is not older than the current one.
+/// If there is none, it is the highest trusted version.
+pub fn into_suggestions(
+    refs: &[PackageRef],
+    versions: &[PackageRef],
+) -> BTreeMap<String, PackageRef> {
+    let mut trusted = HashMap::<_, Vec<(String, &PackageRef)>>::new();
+    for candidate in versions.iter().filter(|r| r.trusted.unwrap_or_default()) {
+        if let Ok(purl) = PackageUrl::from_str(&candidate.purl) {
+            if let Some(version) = purl.version() {
+                trusted
+                    .entry(package_key(&purl))
+                    .or_default()
+                    .push((version.to_string(), candidate));
+            }
+        }
+    }
+
+    let mut result = BTreeMap::new();
+
+    for r in refs.iter().filter(|r| !r.trusted.unwrap_or_default()) {
+        let purl = match PackageUrl::from_str(&r.purl) {
+            Ok(purl) => purl,
+            Err(_) => continue,
+        };
+        let candidates = match trusted.get(&package_key(&purl)) {
+            Some(candidates) => candidates,
+            None => continue,
+        };
+        let current = purl.version().unwrap_or_default();
+
+        let newer = candidates
+            .iter()
+            .filter(|(v, _)| version::compare(purl.ty(), v, current) != Ordering::Less)
+            .min_by(|(a, _), (b, _)| version::compare(purl.ty(), a, b));
+        let nearest = newer.or_else(|| {
+            candidates
+                .iter()
+                .max_by(|(a, _), (b, _)| version::compare(purl.ty(), a, b))
+        });
+
+        if let Some((_, candidate)) = nearest {
+            result.insert(r.purl.clone(), (*candidate).clone());
+        }
+    }
+
+    result
+}
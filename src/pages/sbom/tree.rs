@@ -0,0 +1,261 @@
+use super::model::{Component, SourceFile};
+use crate::{components::Trusted, purl::normalize};
+use patternfly_yew::{
+    next::{TreeNode, TreeTable, TreeTableModel},
+    prelude::*,
+};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+
+/// The status of a component, according to the backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Trusted,
+    Found,
+    Unknown,
+}
+
+impl Status {
+    /// Get the status of a package URL, from the found (normalized) package URLs and if they
+    /// are trusted.
+    pub fn of(purl: &str, found: &HashMap<String, bool>) -> Self {
+        match found.get(&normalize(purl)) {
+            Some(true) => Self::Trusted,
+            Some(false) => Self::Found,
+            None => Self::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> Html {
+        match self {
+            Self::Trusted => html!(<Trusted/>),
+            Self::Found => html!(<Label label="Found" color={Color::Green}/>),
+            Self::Unknown => html!(<Label label="Unknown" color={Color::Grey}/>),
+        }
+    }
+}
+
+/// The dependency graph of a single file.
+struct Graph {
+    components: HashMap<String, Component>,
+    dependencies: HashMap<String, Vec<String>>,
+    /// Found package URLs, and if they are trusted
+    found: Rc<HashMap<String, bool>>,
+}
+
+impl Graph {
+    fn status(&self, component: &Component) -> Option<Status> {
+        let purl = component.purl.as_ref()?;
+        Some(Status::of(purl, &self.found))
+    }
+
+    /// Get a component by its reference, creating a placeholder if the document doesn't have it.
+    fn component(&self, id: &str) -> Component {
+        self.components
+            .get(id)
+            .cloned()
+            .unwrap_or_else(|| Component {
+                id: Some(id.to_string()),
+                name: id.to_string(),
+                ..Default::default()
+            })
+    }
+}
+
+pub struct DependencyTree {
+    files: Vec<Rc<FileNode>>,
+}
+
+impl TreeTableModel for DependencyTree {
+    fn children(&self) -> Vec<Rc<dyn TreeNode>> {
+        self.files
+            .iter()
+            .map(|file| file.clone() as Rc<dyn TreeNode>)
+            .collect()
+    }
+}
+
+impl PartialEq for DependencyTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.files.len() == other.files.len()
+            && self
+                .files
+                .iter()
+                .zip(&other.files)
+                .all(|(a, b)| Rc::ptr_eq(a, b))
+    }
+}
+
+struct FileNode {
+    name: String,
+    graph: Rc<Graph>,
+    roots: Vec<Component>,
+}
+
+impl TreeNode for FileNode {
+    fn render_main(&self) -> Cell {
+        html!(<strong>{ &self.name }</strong>).into()
+    }
+
+    fn render_cell(&self, _context: CellContext) -> Cell {
+        html!().into()
+    }
+
+    fn children(&self) -> Vec<Rc<dyn TreeNode>> {
+        self.roots
+            .iter()
+            .map(|root| {
+                Rc::new(ComponentNode {
+                    graph: self.graph.clone(),
+                    component: root.clone(),
+                    path: Rc::new(vec![]),
+                }) as Rc<dyn TreeNode>
+            })
+            .collect()
+    }
+}
+
+struct ComponentNode {
+    graph: Rc<Graph>,
+    component: Component,
+    /// The references of all parents, to detect cycles
+    path: Rc<Vec<String>>,
+}
+
+impl TreeNode for ComponentNode {
+    fn render_main(&self) -> Cell {
+        html!(
+            <>
+                { &self.component.name }
+                if let Some(status) = self.graph.status(&self.component) {
+                    {" "} { status.label() }
+                }
+            </>
+        )
+        .into()
+    }
+
+    fn render_cell(&self, context: CellContext) -> Cell {
+        match context.column {
+            1 => html!({ self.component.version.clone().unwrap_or_default() }),
+            2 => html!({ self.component.purl.clone().unwrap_or_default() }),
+            _ => html!(),
+        }
+        .into()
+    }
+
+    fn children(&self) -> Vec<Rc<dyn TreeNode>> {
+        let id = match &self.component.id {
+            Some(id) => id,
+            None => return vec![],
+        };
+
+        let mut path = (*self.path).clone();
+        path.push(id.clone());
+        let path = Rc::new(path);
+
+        self.graph
+            .dependencies
+            .get(id)
+            .into_iter()
+            .flatten()
+            // don't follow cycles
+            .filter(|dep| !path.contains(*dep))
+            .map(|dep| {
+                Rc::new(ComponentNode {
+                    graph: self.graph.clone(),
+                    component: self.graph.component(dep),
+                    path: path.clone(),
+                }) as Rc<dyn TreeNode>
+            })
+            .collect()
+    }
+}
+
+/// Build the dependency trees of all files.
+pub fn into_tree(files: &[SourceFile], refs: &[PackageRef]) -> DependencyTree {
+    let found = Rc::new(
+        refs.iter()
+            .map(|r| (normalize(&r.purl), r.trusted.unwrap_or_default()))
+            .collect::<HashMap<_, _>>(),
+    );
+
+    let files = files
+        .iter()
+        .map(|file| {
+            let sbom = &file.sbom;
+
+            let components = sbom
+                .components
+                .iter()
+                .chain(sbom.roots.iter())
+                .filter_map(|c| c.id.clone().map(|id| (id, c.clone())))
+                .collect::<HashMap<_, _>>();
+
+            let dependencies = sbom
+                .dependencies()
+                .into_iter()
+                .map(|(from, to)| {
+                    (
+                        from.to_string(),
+                        to.into_iter().map(ToString::to_string).collect(),
+                    )
+                })
+                .collect::<HashMap<String, Vec<String>>>();
+
+            let roots = match sbom
+                .roots
+                .iter()
+                .any(|root| matches!(&root.id, Some(id) if dependencies.contains_key(id)))
+            {
+                true => sbom.roots.clone(),
+                // if the document doesn't tell us where to start, use everything nobody depends on
+                false => {
+                    let dependents = dependencies.values().flatten().collect::<HashSet<_>>();
+                    sbom.components
+                        .iter()
+                        .filter(|c| matches!(&c.id, Some(id) if !dependents.contains(id)))
+                        .cloned()
+                        .collect()
+                }
+            };
+
+            Rc::new(FileNode {
+                name: file.name.clone(),
+                graph: Rc::new(Graph {
+                    components,
+                    dependencies,
+                    found: found.clone(),
+                }),
+                roots,
+            })
+        })
+        .collect();
+
+    DependencyTree { files }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct DependencyTreeViewProperties {
+    pub tree: Rc<DependencyTree>,
+}
+
+#[function_component(DependencyTreeView)]
+pub fn dependency_tree_view(props: &DependencyTreeViewProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="Component"/>
+        <TableColumn label="Version"/>
+        <TableColumn label="Package URL"/>
+      </TableHeader>
+    );
+
+    html!(
+        <TreeTable<DependencyTree>
+            {header}
+            model={props.tree.clone()}
+        />
+    )
+}
@@ -0,0 +1,199 @@
+use super::model::{Component, SourceFile};
+use crate::{
+    backend::{self, data, PackageService},
+    components::Trusted,
+    hooks::use_backend,
+    pages::AppRoute,
+};
+use packageurl::PackageUrl;
+use patternfly_yew::prelude::*;
+use reqwest::StatusCode;
+use std::rc::Rc;
+use std::str::FromStr;
+use yew::prelude::*;
+use yew_more_hooks::hooks::r#async::*;
+use yew_nested_router::components::Link;
+
+/// A component which doesn't have a package URL.
+#[derive(Clone, PartialEq)]
+pub struct UnidentifiedEntry {
+    name: String,
+    version: Option<String>,
+    group: Option<String>,
+    cpe: Option<String>,
+    hashes: Vec<String>,
+    guess: Option<String>,
+    source: String,
+}
+
+impl TableEntryRenderer for UnidentifiedEntry {
+    fn render_cell(&self, context: &CellContext) -> Cell {
+        match context.column {
+            0 => html!({ &self.name }),
+            1 => html!({ self.version.clone().unwrap_or_default() }),
+            2 => html!({ self.group.clone().unwrap_or_default() }),
+            3 => html!({ self.cpe.clone().unwrap_or_default() }),
+            4 => html!(
+                { for self.hashes.iter().map(|hash| html!(<div><small>{ hash }</small></div>)) }
+            ),
+            5 => html!(
+                if let Some(purl) = &self.guess {
+                    <GuessedPackage purl={purl.clone()} />
+                }
+            ),
+            6 => html!({ &self.source }),
+            _ => html!(),
+        }
+        .into()
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct UnidentifiedPackagesProperties {
+    pub unidentified: Rc<Vec<UnidentifiedEntry>>,
+}
+
+#[function_component(UnidentifiedPackages)]
+pub fn unidentified_packages(props: &UnidentifiedPackagesProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="Name"/>
+        <TableColumn label="Version"/>
+        <TableColumn label="Group"/>
+        <TableColumn label="CPE"/>
+        <TableColumn label="Hashes"/>
+        <TableColumn label="Guessed Package URL"/>
+        <TableColumn label="Source file"/>
+      </TableHeader>
+    );
+
+    let entries = use_memo(
+        |entries| SharedTableModel::new((**entries).clone()),
+        props.unidentified.clone(),
+    );
+
+    html!(
+        <Table<SharedTableModel<UnidentifiedEntry>>
+            {header} entries={(*entries).clone()}
+            mode={TableMode::Compact}
+        >
+        </Table<SharedTableModel<UnidentifiedEntry>>>
+    )
+}
+
+#[derive(Clone, PartialEq, Properties)]
+struct GuessedPackageProperties {
+    purl: String,
+}
+
+/// Show a guessed package URL, which can be looked up on request.
+#[function_component(GuessedPackage)]
+fn guessed_package(props: &GuessedPackageProperties) -> Html {
+    let backend = use_backend();
+
+    let lookup = {
+        let purl = props.purl.clone();
+        use_async_with_options(
+            async move {
+                let purl = match PackageUrl::from_str(&purl) {
+                    Ok(purl) => purl,
+                    Err(_) => return Ok(None),
+                };
+                match PackageService::new((*backend).clone()).lookup(purl).await {
+                    Ok(package) => Ok(Some(package)),
+                    Err(backend::Error::Request(err))
+                        if err.status() == Some(StatusCode::NOT_FOUND) =>
+                    {
+                        Ok(None)
+                    }
+                    Err(err) => Err(err.to_string()),
+                }
+            },
+            UseAsyncOptions::default(),
+        )
+    };
+
+    let onclick = {
+        let lookup = lookup.clone();
+        Callback::from(move |_| lookup.run())
+    };
+
+    html!(
+        <>
+            <code>{ &props.purl }</code>
+            {" "}
+            {
+                match &*lookup {
+                    UseAsyncState::Pending => html!(
+                        <Button variant={ButtonVariant::Link} label="Lookup" {onclick} />
+                    ),
+                    UseAsyncState::Processing => html!(<Spinner size={SpinnerSize::Md} />),
+                    UseAsyncState::Ready(Ok(Some(package))) => html!(
+                        <>
+                            <Link<AppRoute> target={AppRoute::Package { package: props.purl.clone() }}>
+                                { "Found" }
+                            </Link<AppRoute>>
+                            if let data::Package { trusted: Some(true), .. } = package {
+                                {" "} <Trusted/>
+                            }
+                        </>
+                    ),
+                    UseAsyncState::Ready(Ok(None)) => html!(<Label label="Not found" color={Color::Grey} />),
+                    UseAsyncState::Ready(Err(err)) => html!(<Label label={format!("Failed: {err}")} color={Color::Red} />),
+                }
+            }
+        </>
+    )
+}
+
+/// Try guessing a package URL from the type and group of a component.
+fn guess_purl(c: &Component) -> Option<String> {
+    let mut purl = match (c.r#type.as_deref(), c.group.as_deref()) {
+        (Some("container"), _) => PackageUrl::new("oci", c.name.clone()).ok()?,
+        // npm scopes start with an '@'
+        (_, Some(group)) if group.starts_with('@') => {
+            let mut purl = PackageUrl::new("npm", c.name.clone()).ok()?;
+            purl.with_namespace(group.to_string());
+            purl
+        }
+        // Maven group IDs are reverse domain names
+        (Some("library" | "framework" | "application"), Some(group)) if group.contains('.') => {
+            let mut purl = PackageUrl::new("maven", c.name.clone()).ok()?;
+            purl.with_namespace(group.to_string());
+            purl
+        }
+        _ => return None,
+    };
+
+    if let Some(version) = &c.version {
+        purl.with_version(version.clone());
+    }
+
+    Some(purl.to_string())
+}
+
+/// Collect all components which don't have a package URL.
+pub fn into_unidentified(files: &[SourceFile]) -> Vec<UnidentifiedEntry> {
+    files
+        .iter()
+        .flat_map(|file| {
+            file.sbom
+                .components
+                .iter()
+                .filter(|c| c.purl.is_none())
+                .map(|c| UnidentifiedEntry {
+                    name: c.full_name(),
+                    version: c.version.clone(),
+                    group: c.group.clone(),
+                    cpe: c.cpe.clone(),
+                    hashes: c
+                        .hashes
+                        .iter()
+                        .map(|h| format!("{}: {}", h.algorithm, h.value))
+                        .collect(),
+                    guess: guess_purl(c),
+                    source: file.name.clone(),
+                })
+        })
+        .collect()
+}
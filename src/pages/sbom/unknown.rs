@@ -1,19 +1,33 @@
-use cyclonedx_bom::prelude::Bom;
+use super::model::{Component, SourceFile};
+use crate::{components::deps::render_sources, purl::normalize};
 use patternfly_yew::prelude::*;
-use std::collections::BTreeSet;
+use std::collections::BTreeMap;
 use std::rc::Rc;
 use trust_api_model::prelude::*;
 use yew::prelude::*;
 
+#[derive(Clone, PartialEq)]
+struct UnknownRow {
+    entry: UnknownEntry,
+    onselect: Option<Callback<(String, String)>>,
+}
+
 #[derive(Clone, PartialEq)]
 pub struct UnknownEntry {
-    purl: String,
+    pub purl: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub sources: Vec<String>,
 }
 
-impl TableEntryRenderer for UnknownEntry {
+impl TableEntryRenderer for UnknownRow {
     fn render_cell(&self, context: &CellContext) -> Cell {
+        let entry = &self.entry;
         match context.column {
-            0 => html!({ &self.purl }),
+            0 => html!({ &entry.name }),
+            1 => html!({ entry.version.clone().unwrap_or_default() }),
+            2 => html!({ &entry.purl }),
+            3 => render_sources(&entry.purl, &entry.sources, &self.onselect),
             _ => html!(),
         }
         .into()
@@ -23,48 +37,74 @@ impl TableEntryRenderer for UnknownEntry {
 #[derive(Clone, PartialEq, Properties)]
 pub struct UnknownPackagesProperties {
     pub unknown: Rc<Vec<UnknownEntry>>,
+    /// Called with the source file and package URL when a source file is clicked.
+    #[prop_or_default]
+    pub onselect: Option<Callback<(String, String)>>,
 }
 
 #[function_component(UnknownPackages)]
 pub fn unknown_packages(props: &UnknownPackagesProperties) -> Html {
     let header = html_nested!(
       <TableHeader>
+        <TableColumn label="Name"/>
+        <TableColumn label="Version"/>
         <TableColumn label="Package URL"/>
-        <TableColumn/>
+        <TableColumn label="Source file"/>
       </TableHeader>
     );
 
     let entries = use_memo(
-        |entries| SharedTableModel::new((**entries).clone()),
-        props.unknown.clone(),
+        |(entries, onselect)| {
+            SharedTableModel::new(
+                entries
+                    .iter()
+                    .map(|entry| UnknownRow {
+                        entry: entry.clone(),
+                        onselect: onselect.clone(),
+                    })
+                    .collect(),
+            )
+        },
+        (props.unknown.clone(), props.onselect.clone()),
     );
 
     html!(
-        <Table<SharedTableModel<UnknownEntry>>
+        <Table<SharedTableModel<UnknownRow>>
             {header} entries={(*entries).clone()}
             mode={TableMode::Compact}
         >
-        </Table<SharedTableModel<UnknownEntry>>>
+        </Table<SharedTableModel<UnknownRow>>>
     )
 }
 
-pub fn into_unknown(bom: &Bom, refs: &[PackageRef]) -> Vec<UnknownEntry> {
-    let mut components = bom
-        .components
-        .as_ref()
-        .map(|c| {
-            c.0.iter()
-                .filter_map(|c| c.purl.as_ref().map(|purl| purl.to_string()))
-                .collect::<BTreeSet<String>>()
-        })
-        .unwrap_or_default();
+pub fn into_unknown(files: &[SourceFile], refs: &[PackageRef]) -> Vec<UnknownEntry> {
+    // by normalized package URL
+    let mut components = BTreeMap::<String, (&str, &Component, Vec<String>)>::new();
+    for file in files {
+        for c in &file.sbom.components {
+            if let Some(purl) = &c.purl {
+                let (_, _, sources) =
+                    components
+                        .entry(normalize(purl))
+                        .or_insert((purl.as_str(), c, vec![]));
+                if !sources.contains(&file.name) {
+                    sources.push(file.name.clone());
+                }
+            }
+        }
+    }
 
     for found in refs.iter() {
-        components.remove(&found.purl);
+        components.remove(&normalize(&found.purl));
     }
 
     components
         .into_iter()
-        .map(|purl| UnknownEntry { purl })
+        .map(|(_, (purl, c, sources))| UnknownEntry {
+            purl: purl.to_string(),
+            name: c.full_name(),
+            version: c.version.clone(),
+            sources,
+        })
         .collect()
 }
@@ -1,7 +1,14 @@
+use super::{
+    download::{download, Progress},
+    validate::{Issue, Severity},
+};
+use crate::components::count_title;
 use patternfly_yew::{
     next::{TextArea, TextInput},
     prelude::*,
 };
+use std::collections::{BTreeMap, BTreeSet};
+use url::Url;
 use wasm_bindgen_futures::JsFuture;
 use yew::prelude::*;
 use yew_hooks::prelude::*;
@@ -19,12 +26,24 @@ impl From<String> for DropContent {
     fn from(value: String) -> Self {
         if value.is_empty() {
             Self::None
+        } else if is_url(&value) {
+            // dropped or pasted a link as text
+            Self::Uri(value.trim().to_string())
         } else {
             Self::Text(value)
         }
     }
 }
 
+/// Check if the text is a single HTTP(S) URL.
+fn is_url(value: &str) -> bool {
+    let value = value.trim();
+    !value.contains(char::is_whitespace)
+        && Url::parse(value)
+            .map(|url| matches!(url.scheme(), "http" | "https"))
+            .unwrap_or_default()
+}
+
 impl DropContent {
     pub fn is_none(&self) -> bool {
         matches!(self, Self::None)
@@ -50,15 +69,96 @@ impl std::fmt::Display for DropContent {
     }
 }
 
+/// A single document provided by the user.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadedContent {
+    pub name: String,
+    pub content: String,
+}
+
+/// File extensions we consider when selecting a whole folder.
+const SBOM_EXTENSIONS: [&str; 3] = [".json", ".xml", ".spdx"];
+
+/// The number of errors and warnings shown, each.
+const MAX_ISSUES: usize = 50;
+
+/// The number of lines shown before and after an offending line.
+const CONTEXT_LINES: usize = 2;
+
+/// The number of characters shown of a long line, around the offending column.
+const MAX_LINE_LENGTH: usize = 160;
+
+/// An issue of one of the uploaded documents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DocumentIssue {
+    /// The index of the document in the uploaded documents
+    document: usize,
+    issue: Issue,
+}
+
+/// Documents which passed the validation, possibly with warnings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Validated {
+    documents: Vec<UploadedContent>,
+    issues: Vec<DocumentIssue>,
+}
+
+/// Documents which couldn't be loaded or failed the validation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rejected {
+    documents: Vec<UploadedContent>,
+    message: String,
+    issues: Vec<DocumentIssue>,
+}
+
+impl Rejected {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            documents: vec![],
+            message: message.into(),
+            issues: vec![],
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct UploadProperties {
-    pub onsubmit: Callback<String>,
+    pub onsubmit: Callback<Vec<UploadedContent>>,
+    /// Validate a single document, returning all issues found
     #[prop_or(default_validate())]
-    pub onvalidate: Callback<String, Result<String, String>>,
+    pub onvalidate: Callback<String, Vec<Issue>>,
+    /// The label of the submit button
+    #[prop_or(AttrValue::from("Inspect"))]
+    pub submit_label: AttrValue,
+}
+
+fn default_validate() -> Callback<String, Vec<Issue>> {
+    Callback::from(|_| vec![])
+}
+
+/// Create the preview of the documents, marking the start of each file if there is more than one.
+fn preview(documents: &[UploadedContent]) -> String {
+    match documents {
+        [document] => document.content.clone(),
+        documents => documents
+            .iter()
+            .map(|d| format!("==> {} <==\n{}", d.name, d.content))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    }
 }
 
-fn default_validate() -> Callback<String, Result<String, String>> {
-    Callback::from(|data| Ok(data))
+/// Get the files of a file input element, as a vector.
+fn input_files(ele: &web_sys::HtmlInputElement) -> Vec<web_sys::File> {
+    ele.files()
+        .map(|files| {
+            let mut r = Vec::with_capacity(files.length().try_into().unwrap_or_default());
+            for i in 0..files.length() {
+                Extend::extend(&mut r, files.get(i));
+            }
+            r
+        })
+        .unwrap_or_default()
 }
 
 #[function_component(Upload)]
@@ -92,44 +192,88 @@ pub fn upload(props: &UploadProperties) -> Html {
         },
     );
 
-    let processing = use_async_with_cloned_deps(
-        |(content, onvalidate)| async move {
-            let content = match &*content {
-                DropContent::Files(files) => {
-                    let mut content = String::new();
-                    for file in files {
-                        match JsFuture::from(file.text()).await {
-                            Ok(data) => content.push_str(&data.as_string().unwrap_or_default()),
-                            Err(err) => {
-                                return Err((
-                                    String::new(),
-                                    format!(
-                                        "Failed to receive content: {err}",
+    let progress = use_state_eq(|| None::<Progress>);
+
+    let processing = {
+        let progress = progress.clone();
+        use_async_with_cloned_deps(
+            |(content, onvalidate)| async move {
+                let documents = match &*content {
+                    DropContent::Files(files) => {
+                        let mut documents = Vec::with_capacity(files.len());
+                        for file in files {
+                            match JsFuture::from(file.text()).await {
+                                Ok(data) => documents.push(UploadedContent {
+                                    name: file.name(),
+                                    content: data.as_string().unwrap_or_default(),
+                                }),
+                                Err(err) => {
+                                    return Err(Rejected::new(format!(
+                                        "Failed to receive content of '{name}': {err}",
+                                        name = file.name(),
                                         err = err.as_string().unwrap_or_default()
-                                    ),
-                                ))
+                                    )))
+                                }
                             }
                         }
+                        documents
                     }
-                    content
+                    DropContent::Text(text) => vec![UploadedContent {
+                        name: "User Input".to_string(),
+                        content: text.to_string(),
+                    }],
+                    DropContent::Uri(uri) => {
+                        let content = download(uri, |p| progress.set(Some(p))).await;
+                        progress.set(None);
+                        match content {
+                            Ok(content) => vec![UploadedContent {
+                                name: uri.to_string(),
+                                content,
+                            }],
+                            Err(err) => return Err(Rejected::new(err.to_string())),
+                        }
+                    }
+                    DropContent::None => vec![],
+                };
+
+                if documents.iter().all(|d| d.content.is_empty()) {
+                    // return early if the content is empty
+                    return Err(Rejected::new("Requires an SBOM"));
                 }
-                DropContent::Text(text) => text.to_string(),
-                DropContent::Uri(uri) => uri.to_string(),
-                DropContent::None => "".to_string(),
-            };
-
-            if content.is_empty() {
-                // return early if the content is empty
-                Err((String::new(), "Requires an SBOM".to_string()))
-            } else {
-                // return success, as validated JSON
-                onvalidate
-                    .emit(content.clone())
-                    .map_err(|err| (content, err))
-            }
-        },
-        (drop_content.clone(), props.onvalidate.clone()),
-    );
+
+                // validate each document on its own
+                let mut issues = vec![];
+                for (index, document) in documents.iter().enumerate() {
+                    issues.extend(onvalidate.emit(document.content.clone()).into_iter().map(
+                        |issue| DocumentIssue {
+                            document: index,
+                            issue,
+                        },
+                    ));
+                }
+
+                let errors = issues
+                    .iter()
+                    .filter(|i| i.issue.severity == Severity::Error)
+                    .count();
+                if errors == 0 {
+                    // return success, as validated documents
+                    Ok(Validated { documents, issues })
+                } else {
+                    let message = match (documents.len(), errors) {
+                        (1, 1) => issues[0].issue.to_string(),
+                        _ => format!("Found {}", count_title(errors, "error", "errors")),
+                    };
+                    Err(Rejected {
+                        documents,
+                        message,
+                        issues,
+                    })
+                }
+            },
+            (drop_content.clone(), props.onvalidate.clone()),
+        )
+    };
 
     let onclear = {
         let drop_content = drop_content.clone();
@@ -141,26 +285,49 @@ pub fn upload(props: &UploadProperties) -> Html {
 
     let helper_text = processing
         .error()
-        .map(|err| FormHelperText::from((err.1.to_string(), InputState::Error)));
+        .map(|err| FormHelperText::from((err.message.clone(), InputState::Error)))
+        .or_else(|| {
+            (*progress).map(|p| FormHelperText::from((p.to_string(), InputState::Default)))
+        });
     let state = helper_text
         .as_ref()
         .map(|h| h.input_state)
         .unwrap_or_default();
+    let (documents, issues) = match &*processing {
+        UseAsyncState::Ready(Ok(validated)) => (&validated.documents[..], &validated.issues[..]),
+        UseAsyncState::Ready(Err(rejected)) => (&rejected.documents[..], &rejected.issues[..]),
+        _ => (&[][..], &[][..]),
+    };
     let content = use_memo(
         |processing| match &**processing {
-            UseAsyncState::Ready(Ok(content)) => content.clone(),
-            UseAsyncState::Ready(Err((content, _))) => content.clone(),
+            UseAsyncState::Ready(Ok(validated)) => preview(&validated.documents),
+            UseAsyncState::Ready(Err(rejected)) => preview(&rejected.documents),
             _ => String::new(),
         },
         processing.clone(),
     );
 
+    let errors = issues
+        .iter()
+        .filter(|i| i.issue.severity == Severity::Error)
+        .take(MAX_ISSUES)
+        .collect::<Vec<_>>();
+    let warnings = issues
+        .iter()
+        .filter(|i| i.issue.severity == Severity::Warning)
+        .take(MAX_ISSUES)
+        .collect::<Vec<_>>();
+    let located = errors
+        .iter()
+        .chain(&warnings)
+        .any(|i| i.issue.position.is_some());
+
     let onsubmit = {
         let processing = processing.clone();
         let onsubmit = props.onsubmit.clone();
         Callback::from(move |_| {
             if let Some(data) = processing.data() {
-                onsubmit.emit(data.clone());
+                onsubmit.emit(data.documents.clone());
             }
         })
     };
@@ -180,17 +347,34 @@ pub fn upload(props: &UploadProperties) -> Html {
         let drop_content = drop_content.clone();
         Callback::from(move |_| {
             if let Some(ele) = file_input_ref.cast::<web_sys::HtmlInputElement>() {
-                let files = ele
-                    .files()
-                    .map(|files| {
-                        let mut r =
-                            Vec::with_capacity(files.length().try_into().unwrap_or_default());
-                        for i in 0..files.length() {
-                            Extend::extend(&mut r, files.get(i));
-                        }
-                        r
+                drop_content.set(DropContent::Files(input_files(&ele)));
+            }
+        })
+    };
+
+    let folder_input_ref = use_node_ref();
+    let onopen_folder = {
+        let folder_input_ref = folder_input_ref.clone();
+        Callback::from(move |_| {
+            if let Some(ele) = folder_input_ref.cast::<web_sys::HtmlElement>() {
+                ele.click();
+            }
+        })
+    };
+
+    let onchange_open_folder = {
+        let folder_input_ref = folder_input_ref.clone();
+        let drop_content = drop_content.clone();
+        Callback::from(move |_| {
+            if let Some(ele) = folder_input_ref.cast::<web_sys::HtmlInputElement>() {
+                // a release folder might contain more than just SBOMs
+                let files = input_files(&ele)
+                    .into_iter()
+                    .filter(|file| {
+                        let name = file.name().to_lowercase();
+                        SBOM_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
                     })
-                    .unwrap_or_default();
+                    .collect();
                 drop_content.set(DropContent::Files(files));
             }
         })
@@ -215,7 +399,8 @@ pub fn upload(props: &UploadProperties) -> Html {
                     <FileUploadSelect>
                         <InputGroup>
                             <TextInput readonly=true value={(*drop_content).to_string()}/>
-                            <input ref={file_input_ref.clone()} style="display: none;" type="file" onchange={onchange_open} />
+                            <input ref={file_input_ref.clone()} style="display: none;" type="file" multiple=true onchange={onchange_open} />
+                            <input ref={folder_input_ref.clone()} style="display: none;" type="file" webkitdirectory="" onchange={onchange_open_folder} />
                             <Button
                                 variant={ButtonVariant::Control}
                                 disabled={processing.is_processing()}
@@ -223,12 +408,19 @@ pub fn upload(props: &UploadProperties) -> Html {
                             >
                                 {"Open"}
                             </Button>
+                            <Button
+                                variant={ButtonVariant::Control}
+                                disabled={processing.is_processing()}
+                                onclick={onopen_folder}
+                            >
+                                {"Open folder"}
+                            </Button>
                             <Button
                                 variant={ButtonVariant::Control}
                                 disabled={state == InputState::Error}
                                 onclick={onsubmit}
                             >
-                                {"Inspect"}
+                                { &props.submit_label }
                             </Button>
                             <Button
                                 variant={ButtonVariant::Control}
@@ -242,6 +434,9 @@ pub fn upload(props: &UploadProperties) -> Html {
                         processing={processing.is_processing()}
                         invalid={state == InputState::Error}
                     >
+                        if located {
+                            { excerpts(documents, errors.iter().chain(&warnings).copied()) }
+                        }
                         <TextArea
                             value={(*content).clone()}
                             resize={ResizeOrientation::Vertical}
@@ -253,6 +448,149 @@ pub fn upload(props: &UploadProperties) -> Html {
                     </FileUploadDetails>
                 </FileUpload>
             </FormGroup>
+            { issue_list("Errors", AlertType::Danger, &errors, issues, Severity::Error, documents) }
+            { issue_list("Warnings", AlertType::Warning, &warnings, issues, Severity::Warning, documents) }
         </Form>
     )
 }
+
+/// Render a list of issues, noting how many more there are.
+fn issue_list(
+    title: &str,
+    r#type: AlertType,
+    shown: &[&DocumentIssue],
+    all: &[DocumentIssue],
+    severity: Severity,
+    documents: &[UploadedContent],
+) -> Html {
+    if shown.is_empty() {
+        return html!();
+    }
+
+    let total = all.iter().filter(|i| i.issue.severity == severity).count();
+    html!(
+        <Alert {r#type} inline=true title={format!("{title} ({total})")}>
+            <ul>
+                { for shown.iter().map(|i| html!(
+                    <li>
+                        if documents.len() > 1 {
+                            if let Some(document) = documents.get(i.document) {
+                                <strong>{ &document.name }</strong>{ ": " }
+                            }
+                        }
+                        if let Some(pointer) = &i.issue.pointer {
+                            <code>{ if pointer.is_empty() { "/" } else { pointer.as_str() } }</code>{ " " }
+                        }
+                        if let Some(position) = &i.issue.position {
+                            { format!("(line {}, column {}) ", position.line, position.column) }
+                        }
+                        { &i.issue.message }
+                    </li>
+                )) }
+                if total > shown.len() {
+                    <li>{ format!("… and {} more", total - shown.len()) }</li>
+                }
+            </ul>
+        </Alert>
+    )
+}
+
+/// Get (part of) a line, keeping the column in view for long lines.
+fn excerpt_line(line: &str, column: Option<usize>) -> String {
+    let len = line.chars().count();
+    if len <= MAX_LINE_LENGTH {
+        return line.to_string();
+    }
+
+    let start = column
+        .unwrap_or(1)
+        .saturating_sub(MAX_LINE_LENGTH / 2)
+        .min(len - MAX_LINE_LENGTH);
+    let excerpt = line
+        .chars()
+        .skip(start)
+        .take(MAX_LINE_LENGTH)
+        .collect::<String>();
+
+    format!(
+        "{}{excerpt}{}",
+        if start > 0 { "…" } else { "" },
+        if start + MAX_LINE_LENGTH < len {
+            "…"
+        } else {
+            ""
+        }
+    )
+}
+
+/// Render the offending lines of the documents, with some lines of context.
+fn excerpts<'a>(
+    documents: &[UploadedContent],
+    issues: impl Iterator<Item = &'a DocumentIssue>,
+) -> Html {
+    // the most severe issue of each line, by document
+    let mut lines = BTreeMap::<usize, BTreeMap<usize, (Severity, usize)>>::new();
+    for i in issues {
+        if let Some(position) = &i.issue.position {
+            let entry = lines
+                .entry(i.document)
+                .or_default()
+                .entry(position.line)
+                .or_insert((i.issue.severity, position.column));
+            if i.issue.severity < entry.0 {
+                *entry = (i.issue.severity, position.column);
+            }
+        }
+    }
+
+    html!({
+        for documents.iter().enumerate().filter_map(|(index, document)| {
+            let marked = lines.get(&index)?;
+            let text = document.content.lines().collect::<Vec<_>>();
+
+            let mut shown = BTreeSet::new();
+            for line in marked.keys() {
+                let start = line.saturating_sub(CONTEXT_LINES).max(1);
+                shown.extend(start..=(line + CONTEXT_LINES).min(text.len()));
+            }
+
+            let mut rows = vec![];
+            let mut last = 0;
+            for line in shown {
+                if last > 0 && line > last + 1 {
+                    rows.push(html!(<div>{ "⋮" }</div>));
+                }
+                last = line;
+
+                let content = text.get(line - 1).copied().unwrap_or_default();
+                let (style, column) = match marked.get(&line) {
+                    Some((Severity::Error, column)) => (
+                        "background-color: var(--pf-global--palette--red-50);",
+                        Some(*column),
+                    ),
+                    Some((Severity::Warning, column)) => (
+                        "background-color: var(--pf-global--palette--gold-50);",
+                        Some(*column),
+                    ),
+                    None => ("", None),
+                };
+                rows.push(html!(
+                    <div {style}>{ format!("{line:>6} | {}", excerpt_line(content, column)) }</div>
+                ));
+            }
+
+            Some(html!(
+                <>
+                    if documents.len() > 1 {
+                        <Title level={Level::H4}>{ &document.name }</Title>
+                    }
+                    <CodeBlock>
+                        <CodeBlockCode>
+                            { for rows }
+                        </CodeBlockCode>
+                    </CodeBlock>
+                </>
+            ))
+        })
+    })
+}
@@ -0,0 +1,729 @@
+//! Validating uploaded SBOMs, reporting the location of each problem.
+//!
+//! CycloneDX JSON documents get some basic structural checks, which tell us about all problems
+//! of a document, and not only the first one the parser runs into. This is not a validation
+//! against the CycloneDX JSON schema, it only checks:
+//!
+//! * the `bomFormat`, `specVersion`, `serialNumber` and `version` of the document
+//! * the required properties and the types of the properties of components, hashes, licenses,
+//!   external references, dependencies and vulnerabilities which are used when inspecting an SBOM
+//! * the allowed values of component types, scopes and hash algorithms
+//! * that the `bom-ref` of components is unique, and the format of package URLs
+//!
+//! Unknown properties, and everything else the schema defines, are not checked. Other documents
+//! are checked by parsing them.
+
+use super::parse::{self, Format, SpecVersion};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The document can't be used
+    Error,
+    /// The document can be used, but is missing information
+    Warning,
+}
+
+/// A position in the document, starting with line and column 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A problem found in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// The JSON pointer to the offending value
+    pub pointer: Option<String>,
+    pub position: Option<Position>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(pointer) = &self.pointer {
+            write!(
+                f,
+                "{} ",
+                if pointer.is_empty() {
+                    "/"
+                } else {
+                    pointer.as_str()
+                }
+            )?;
+        }
+        if let Some(Position { line, column }) = &self.position {
+            write!(f, "(line {line}, column {column}) ")?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// The hash algorithms of the CycloneDX specification.
+const HASH_ALGORITHMS: [&str; 12] = [
+    "MD5",
+    "SHA-1",
+    "SHA-256",
+    "SHA-384",
+    "SHA-512",
+    "SHA3-256",
+    "SHA3-384",
+    "SHA3-512",
+    "BLAKE2b-256",
+    "BLAKE2b-384",
+    "BLAKE2b-512",
+    "BLAKE3",
+];
+
+/// The component types of all supported versions.
+const COMPONENT_TYPES: [&str; 8] = [
+    "application",
+    "framework",
+    "library",
+    "container",
+    "operating-system",
+    "device",
+    "firmware",
+    "file",
+];
+
+/// The component types added with 1.5.
+const COMPONENT_TYPES_1_5: [&str; 4] = [
+    "platform",
+    "device-driver",
+    "machine-learning-model",
+    "data",
+];
+
+const SCOPES: [&str; 3] = ["required", "optional", "excluded"];
+
+/// Validate a document, detecting the format from the content.
+pub fn validate(data: &str) -> Vec<Issue> {
+    let mut issues = match Format::detect(data) {
+        Some(Format::Json) => match serde_json::from_str::<Value>(data) {
+            Ok(value) if value.get("spdxVersion").is_none() => validate_cyclonedx(data, &value),
+            Ok(_) => vec![],
+            Err(err) => {
+                return vec![Issue {
+                    severity: Severity::Error,
+                    pointer: None,
+                    position: Some(Position {
+                        line: err.line(),
+                        column: err.column(),
+                    }),
+                    message: format!("Invalid JSON: {err}"),
+                }]
+            }
+        },
+        Some(Format::Xml) => match roxmltree::Document::parse(data) {
+            Ok(_) => vec![],
+            Err(err) => {
+                let pos = err.pos();
+                return vec![Issue {
+                    severity: Severity::Error,
+                    pointer: None,
+                    position: Some(Position {
+                        line: pos.row as usize,
+                        column: pos.col as usize,
+                    }),
+                    message: format!("Invalid XML: {err}"),
+                }];
+            }
+        },
+        _ => vec![],
+    };
+
+    // the checks don't cover everything the parser expects
+    if !issues.iter().any(|issue| issue.severity == Severity::Error) {
+        if let Err(err) = parse::parse(data) {
+            issues.push(Issue {
+                severity: Severity::Error,
+                pointer: None,
+                position: None,
+                message: err.to_string(),
+            });
+        }
+    }
+
+    issues.sort_by_key(|issue| (issue.severity, issue.position.map(|p| (p.line, p.column))));
+    issues
+}
+
+fn validate_cyclonedx(data: &str, value: &Value) -> Vec<Issue> {
+    let mut validator = Validator::default();
+    validator.bom(value);
+
+    let positions = Locator::new(data).locate();
+    validator
+        .issues
+        .into_iter()
+        .map(|(severity, pointer, message)| Issue {
+            severity,
+            position: positions.get(&pointer).copied(),
+            pointer: Some(pointer),
+            message,
+        })
+        .collect()
+}
+
+/// Escape a property name for a JSON pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Checks a CycloneDX JSON document, collecting the severity, pointer and message of all issues.
+#[derive(Default)]
+struct Validator {
+    issues: Vec<(Severity, String, String)>,
+    spec_version: Option<SpecVersion>,
+    bom_refs: HashSet<String>,
+}
+
+impl Validator {
+    fn error(&mut self, pointer: &str, message: impl Into<String>) {
+        self.issues
+            .push((Severity::Error, pointer.to_string(), message.into()));
+    }
+
+    fn warning(&mut self, pointer: &str, message: impl Into<String>) {
+        self.issues
+            .push((Severity::Warning, pointer.to_string(), message.into()));
+    }
+
+    fn object<'v>(&mut self, pointer: &str, value: &'v Value) -> Option<&'v Map<String, Value>> {
+        let result = value.as_object();
+        if result.is_none() {
+            self.error(pointer, "expected an object");
+        }
+        result
+    }
+
+    /// Get the items of an optional array.
+    fn array<'v>(
+        &mut self,
+        pointer: &str,
+        object: &'v Map<String, Value>,
+        key: &str,
+    ) -> Vec<(String, &'v Value)> {
+        let pointer = format!("{pointer}/{}", escape(key));
+        let items = match object.get(key) {
+            None => &[][..],
+            Some(Value::Array(items)) => items.as_slice(),
+            Some(_) => {
+                self.error(&pointer, "expected an array");
+                &[][..]
+            }
+        };
+        items
+            .iter()
+            .enumerate()
+            .map(|(n, item)| (format!("{pointer}/{n}"), item))
+            .collect()
+    }
+
+    /// Get an optional string, reporting values of other types.
+    fn string<'v>(
+        &mut self,
+        pointer: &str,
+        object: &'v Map<String, Value>,
+        key: &str,
+    ) -> Option<&'v str> {
+        match object.get(key) {
+            None => None,
+            Some(Value::String(value)) => Some(value),
+            Some(_) => {
+                self.error(&format!("{pointer}/{}", escape(key)), "expected a string");
+                None
+            }
+        }
+    }
+
+    /// Get a required string, reporting it if missing.
+    fn required<'v>(
+        &mut self,
+        pointer: &str,
+        object: &'v Map<String, Value>,
+        key: &str,
+    ) -> Option<&'v str> {
+        if !object.contains_key(key) {
+            self.error(pointer, format!("missing required property '{key}'"));
+        }
+        self.string(pointer, object, key)
+    }
+
+    /// Check that an optional string is one of the allowed values.
+    fn one_of(&mut self, pointer: &str, object: &Map<String, Value>, key: &str, allowed: &[&str]) {
+        if let Some(value) = self.string(pointer, object, key) {
+            if !allowed.contains(&value) {
+                self.error(
+                    &format!("{pointer}/{}", escape(key)),
+                    format!("'{value}' is not one of: {}", allowed.join(", ")),
+                );
+            }
+        }
+    }
+
+    fn bom(&mut self, value: &Value) {
+        let bom = match self.object("", value) {
+            Some(bom) => bom,
+            None => return,
+        };
+
+        if let Some(format) = self.required("", bom, "bomFormat") {
+            if format != "CycloneDX" {
+                self.error(
+                    "/bomFormat",
+                    format!("expected 'CycloneDX', found '{format}'"),
+                );
+            }
+        }
+        if let Some(version) = self.required("", bom, "specVersion") {
+            self.spec_version = SpecVersion::from_str(version).ok();
+        }
+        if let Some(serial) = self.string("", bom, "serialNumber") {
+            if !is_serial_number(serial) {
+                self.error(
+                    "/serialNumber",
+                    "expected a URN in the format 'urn:uuid:<uuid>'",
+                );
+            }
+        }
+        match bom.get("version") {
+            None => {}
+            Some(version) if version.as_u64().map(|v| v >= 1).unwrap_or_default() => {}
+            Some(_) => self.error("/version", "expected an integer of at least 1"),
+        }
+
+        if let Some(metadata) = bom.get("metadata") {
+            if let Some(metadata) = self.object("/metadata", metadata) {
+                self.string("/metadata", metadata, "timestamp");
+                if let Some(component) = metadata.get("component") {
+                    self.component("/metadata/component", component);
+                }
+            }
+        }
+
+        for (pointer, component) in self.array("", bom, "components") {
+            self.component(&pointer, component);
+        }
+        for (pointer, reference) in self.array("", bom, "externalReferences") {
+            self.external_reference(&pointer, reference);
+        }
+        for (pointer, dependency) in self.array("", bom, "dependencies") {
+            self.dependency(&pointer, dependency);
+        }
+        for (pointer, vulnerability) in self.array("", bom, "vulnerabilities") {
+            self.vulnerability(&pointer, vulnerability);
+        }
+    }
+
+    fn component(&mut self, pointer: &str, value: &Value) {
+        let component = match self.object(pointer, value) {
+            Some(component) => component,
+            None => return,
+        };
+
+        match self.spec_version {
+            Some(SpecVersion::V1_5) => {
+                let types = COMPONENT_TYPES
+                    .iter()
+                    .chain(&COMPONENT_TYPES_1_5)
+                    .copied()
+                    .collect::<Vec<_>>();
+                self.one_of(pointer, component, "type", &types);
+            }
+            _ => self.one_of(pointer, component, "type", &COMPONENT_TYPES),
+        }
+        if !component.contains_key("type") {
+            self.error(pointer, "missing required property 'type'");
+        }
+
+        let name = self
+            .required(pointer, component, "name")
+            .unwrap_or_default();
+
+        self.string(pointer, component, "version");
+        if !component.contains_key("version") {
+            match self.spec_version {
+                Some(SpecVersion::V1_3) => {
+                    self.error(pointer, "missing required property 'version'")
+                }
+                _ => self.warning(pointer, format!("component '{name}' has no version")),
+            }
+        }
+
+        match self.string(pointer, component, "bom-ref") {
+            Some(bom_ref) => {
+                if !self.bom_refs.insert(bom_ref.to_string()) {
+                    self.error(
+                        &format!("{pointer}/bom-ref"),
+                        format!("duplicate bom-ref '{bom_ref}'"),
+                    );
+                }
+            }
+            None if !component.contains_key("bom-ref") => {
+                self.warning(pointer, format!("component '{name}' has no bom-ref"))
+            }
+            None => {}
+        }
+
+        self.string(pointer, component, "group");
+        self.string(pointer, component, "cpe");
+        if let Some(purl) = self.string(pointer, component, "purl") {
+            if packageurl::PackageUrl::from_str(purl).is_err() {
+                self.warning(
+                    &format!("{pointer}/purl"),
+                    format!("invalid package URL '{purl}'"),
+                );
+            }
+        }
+        self.one_of(pointer, component, "scope", &SCOPES);
+
+        for (pointer, hash) in self.array(pointer, component, "hashes") {
+            self.hash(&pointer, hash);
+        }
+        for (pointer, license) in self.array(pointer, component, "licenses") {
+            self.license(&pointer, license);
+        }
+        for (pointer, reference) in self.array(pointer, component, "externalReferences") {
+            self.external_reference(&pointer, reference);
+        }
+        for (pointer, child) in self.array(pointer, component, "components") {
+            self.component(&pointer, child);
+        }
+    }
+
+    fn hash(&mut self, pointer: &str, value: &Value) {
+        if let Some(hash) = self.object(pointer, value) {
+            if !hash.contains_key("alg") {
+                self.error(pointer, "missing required property 'alg'");
+            }
+            self.one_of(pointer, hash, "alg", &HASH_ALGORITHMS);
+            if let Some(content) = self.required(pointer, hash, "content") {
+                if content.is_empty() || !content.chars().all(|c| c.is_ascii_hexdigit()) {
+                    self.error(
+                        &format!("{pointer}/content"),
+                        "expected a hexadecimal value",
+                    );
+                }
+            }
+        }
+    }
+
+    fn license(&mut self, pointer: &str, value: &Value) {
+        let choice = match self.object(pointer, value) {
+            Some(choice) => choice,
+            None => return,
+        };
+
+        match (choice.get("license"), choice.get("expression")) {
+            (Some(license), None) => {
+                let pointer = format!("{pointer}/license");
+                if let Some(license) = self.object(&pointer, license) {
+                    let id = self.string(&pointer, license, "id");
+                    let name = self.string(&pointer, license, "name");
+                    match (id, name) {
+                        (Some(_), Some(_)) => {
+                            self.error(&pointer, "expected either 'id' or 'name', not both")
+                        }
+                        (None, None) => self.error(&pointer, "expected either 'id' or 'name'"),
+                        _ => {}
+                    }
+                }
+            }
+            (None, Some(_)) => {
+                self.string(pointer, choice, "expression");
+            }
+            (Some(_), Some(_)) => self.error(
+                pointer,
+                "expected either 'license' or 'expression', not both",
+            ),
+            (None, None) => self.error(pointer, "expected either 'license' or 'expression'"),
+        }
+    }
+
+    fn external_reference(&mut self, pointer: &str, value: &Value) {
+        if let Some(reference) = self.object(pointer, value) {
+            self.required(pointer, reference, "url");
+            self.required(pointer, reference, "type");
+        }
+    }
+
+    fn dependency(&mut self, pointer: &str, value: &Value) {
+        if let Some(dependency) = self.object(pointer, value) {
+            self.required(pointer, dependency, "ref");
+            for (pointer, item) in self.array(pointer, dependency, "dependsOn") {
+                if !item.is_string() {
+                    self.error(&pointer, "expected a string");
+                }
+            }
+        }
+    }
+
+    fn vulnerability(&mut self, pointer: &str, value: &Value) {
+        if let Some(vulnerability) = self.object(pointer, value) {
+            self.string(pointer, vulnerability, "bom-ref");
+            self.string(pointer, vulnerability, "id");
+            for (pointer, affects) in self.array(pointer, vulnerability, "affects") {
+                if let Some(affects) = self.object(&pointer, affects) {
+                    self.required(&pointer, affects, "ref");
+                }
+            }
+        }
+    }
+}
+
+/// Check for the format `urn:uuid:xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+fn is_serial_number(value: &str) -> bool {
+    let uuid = match value.strip_prefix("urn:uuid:") {
+        Some(uuid) => uuid,
+        None => return false,
+    };
+
+    let groups = uuid.split('-').collect::<Vec<_>>();
+    groups.iter().map(|g| g.len()).eq([8, 4, 4, 4, 12])
+        && groups
+            .iter()
+            .all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Finds the position of all values of a (valid) JSON document, by their JSON pointer.
+struct Locator<'a> {
+    data: &'a [u8],
+    pos: usize,
+    line: usize,
+    column: usize,
+    positions: HashMap<String, Position>,
+}
+
+impl<'a> Locator<'a> {
+    fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            pos: 0,
+            line: 1,
+            column: 1,
+            positions: HashMap::new(),
+        }
+    }
+
+    fn locate(mut self) -> HashMap<String, Position> {
+        // the document was valid before, so we can ignore errors
+        let _ = self.value(&mut String::new());
+        self.positions
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        if b == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if b & 0xC0 != 0x80 {
+            // count characters, not the continuation bytes of UTF-8
+            self.column += 1;
+        }
+        Some(b)
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        match self.bump()? == expected {
+            true => Some(()),
+            false => None,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\r' | b'\n')) {
+            self.bump();
+        }
+    }
+
+    fn value(&mut self, pointer: &mut String) -> Option<()> {
+        self.skip_whitespace();
+        self.positions.insert(
+            pointer.clone(),
+            Position {
+                line: self.line,
+                column: self.column,
+            },
+        );
+
+        match self.peek()? {
+            b'{' => self.object(pointer),
+            b'[' => self.array(pointer),
+            b'"' => self.string().map(|_| ()),
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b',' | b']' | b'}' | b' ' | b'\t' | b'\r' | b'\n')
+                ) {
+                    self.bump();
+                }
+                Some(())
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.expect(b'"')?;
+        loop {
+            match self.bump()? {
+                b'\\' => {
+                    self.bump()?;
+                }
+                b'"' => break,
+                _ => {}
+            }
+        }
+        serde_json::from_slice(&self.data[start..self.pos]).ok()
+    }
+
+    fn object(&mut self, pointer: &mut String) -> Option<()> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.bump();
+            return Some(());
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+
+            let len = pointer.len();
+            pointer.push('/');
+            pointer.push_str(&escape(&key));
+            self.value(pointer)?;
+            pointer.truncate(len);
+
+            self.skip_whitespace();
+            match self.bump()? {
+                b',' => continue,
+                b'}' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &mut String) -> Option<()> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.bump();
+            return Some(());
+        }
+
+        let mut n = 0;
+        loop {
+            let len = pointer.len();
+            pointer.push_str(&format!("/{n}"));
+            self.value(pointer)?;
+            pointer.truncate(len);
+            n += 1;
+
+            self.skip_whitespace();
+            match self.bump()? {
+                b',' => continue,
+                b']' => return Some(()),
+                _ => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(data: &str) -> Vec<Issue> {
+        validate_cyclonedx(data, &serde_json::from_str(data).unwrap())
+    }
+
+    #[test]
+    fn test_valid() {
+        let data = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.4",
+  "version": 1,
+  "components": [
+    { "type": "library", "bom-ref": "a", "name": "a", "version": "1.0" }
+  ]
+}"#;
+        assert_eq!(issues(data), vec![]);
+    }
+
+    #[test]
+    fn test_located_issues() {
+        let data = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.4",
+  "components": [
+    { "type": "library", "bom-ref": "a", "version": "1.0" },
+    { "type": "unknown", "bom-ref": "a", "name": "b" }
+  ]
+}"#;
+        let issues = issues(data);
+        let messages = issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.severity,
+                    issue.pointer.as_deref().unwrap_or_default(),
+                    issue.position.map(|p| p.line),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                (Severity::Error, "/components/0", Some(5)),
+                (Severity::Error, "/components/1/type", Some(6)),
+                (Severity::Warning, "/components/1", Some(6)),
+                (Severity::Error, "/components/1/bom-ref", Some(6)),
+            ]
+        );
+    }
+
+    /// Documents which are invalid according to the schema, but pass the basic checks.
+    #[test]
+    fn test_schema_invalid() {
+        // an unknown property, which the schema doesn't allow
+        let data = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.4",
+  "unknown": true,
+  "components": [
+    { "type": "library", "bom-ref": "a", "name": "a", "version": "1.0", "extra": 1 }
+  ]
+}"#;
+        assert_eq!(issues(data), vec![]);
+
+        // an external reference type which isn't defined by the schema
+        let data = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.4",
+  "externalReferences": [ { "type": "unknown", "url": "https://example.com" } ]
+}"#;
+        assert_eq!(issues(data), vec![]);
+
+        // a timestamp which isn't a date-time
+        let data = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.4",
+  "metadata": { "timestamp": "yesterday" }
+}"#;
+        assert_eq!(issues(data), vec![]);
+    }
+}
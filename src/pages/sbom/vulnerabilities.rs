@@ -0,0 +1,120 @@
+use super::model::SourceFile;
+use crate::{pages::AppRoute, purl::normalize};
+use patternfly_yew::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use trust_api_model::prelude::*;
+use yew::prelude::*;
+use yew_nested_router::components::Link;
+
+/// A vulnerability, together with the components of the SBOM it affects.
+#[derive(Clone, PartialEq)]
+pub struct VulnerabilityEntry {
+    cve: String,
+    components: Vec<AffectedComponent>,
+}
+
+#[derive(Clone, PartialEq)]
+struct AffectedComponent {
+    name: String,
+    purl: String,
+}
+
+impl TableEntryRenderer for VulnerabilityEntry {
+    fn render_cell(&self, context: &CellContext) -> Cell {
+        match context.column {
+            0 => html!(
+                <Link<AppRoute> target={AppRoute::Vulnerability { cve: self.cve.clone() }}>
+                    { &self.cve }
+                </Link<AppRoute>>
+            ),
+            1 => html!(
+                <List r#type={ListType::Plain}>
+                    { for self.components.iter().map(|c| html!(
+                        <Link<AppRoute> target={AppRoute::Package { package: c.purl.clone() }}>
+                            { &c.name } {" "} <small>{ &c.purl }</small>
+                        </Link<AppRoute>>
+                    )) }
+                </List>
+            ),
+            _ => html!(),
+        }
+        .into()
+    }
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct VulnerabilitiesProperties {
+    pub vulnerabilities: Rc<Vec<VulnerabilityEntry>>,
+}
+
+#[function_component(Vulnerabilities)]
+pub fn vulnerabilities(props: &VulnerabilitiesProperties) -> Html {
+    let header = html_nested!(
+      <TableHeader>
+        <TableColumn label="Vulnerability"/>
+        <TableColumn label="Affected components"/>
+      </TableHeader>
+    );
+
+    let entries = use_memo(
+        |entries| SharedTableModel::new((**entries).clone()),
+        props.vulnerabilities.clone(),
+    );
+
+    html!(
+        if props.vulnerabilities.is_empty() {
+            <Bullseye>
+                <EmptyState
+                    title="No vulnerabilities"
+                    icon={Icon::CheckCircle}
+                >
+                    { "None of the found components has a known vulnerability." }
+                </EmptyState>
+            </Bullseye>
+        } else {
+            <Table<SharedTableModel<VulnerabilityEntry>>
+                {header} entries={(*entries).clone()}
+                mode={TableMode::Compact}
+            >
+            </Table<SharedTableModel<VulnerabilityEntry>>>
+        }
+    )
+}
+
+/// Collect the vulnerabilities of the packages, mapping them back to the components of the SBOM.
+pub fn into_vulnerabilities(files: &[SourceFile], packages: &[Package]) -> Vec<VulnerabilityEntry> {
+    let names = files
+        .iter()
+        .flat_map(|file| file.sbom.components.iter())
+        .filter_map(|c| {
+            c.purl
+                .as_deref()
+                .map(|purl| (normalize(purl), c.full_name()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut result = BTreeMap::<String, Vec<AffectedComponent>>::new();
+    for package in packages {
+        let purl = match &package.purl {
+            Some(purl) => purl,
+            None => continue,
+        };
+        let name = names.get(&normalize(purl)).unwrap_or(purl);
+
+        for vuln in &package.vulnerabilities {
+            result
+                .entry(vuln.cve.clone())
+                .or_default()
+                .push(AffectedComponent {
+                    name: name.to_string(),
+                    purl: purl.clone(),
+                });
+        }
+    }
+
+    result
+        .into_iter()
+        .map(|(cve, components)| VulnerabilityEntry { cve, components })
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Normalizing package URLs, so that they can be compared.
+//!
+//! The same package can be written in different ways, e.g. with the qualifiers in a different
+//! order, or with different percent-encoding. The canonical form follows the rules of the
+//! package URL specification.
+
+use itertools::Itertools;
+use packageurl::PackageUrl;
+use std::str::FromStr;
+
+/// Types for which namespace and name are case-insensitive, and must be lowercased.
+const CASE_INSENSITIVE: [&str; 3] = ["bitbucket", "composer", "github"];
+
+/// Types for which only the name is case-insensitive, and must be lowercased.
+const CASE_INSENSITIVE_NAME: [&str; 5] = ["alpm", "apk", "deb", "hex", "pypi"];
+
+/// Get the canonical form of a package URL, or the input if it can't be parsed.
+pub fn normalize(purl: &str) -> String {
+    canonical(purl).unwrap_or_else(|| purl.to_string())
+}
+
+fn canonical(purl: &str) -> Option<String> {
+    let purl = PackageUrl::from_str(purl).ok()?;
+
+    let ty = purl.ty().to_ascii_lowercase();
+    let mut namespace = purl.namespace().map(ToString::to_string);
+    let mut name = purl.name().to_string();
+
+    if CASE_INSENSITIVE.contains(&ty.as_str()) {
+        namespace = namespace.map(|namespace| namespace.to_lowercase());
+        name = name.to_lowercase();
+    } else if CASE_INSENSITIVE_NAME.contains(&ty.as_str()) {
+        name = name.to_lowercase();
+    }
+    if ty == "pypi" {
+        name = name.replace('_', "-");
+    }
+
+    let mut result = PackageUrl::new(ty, name).ok()?;
+    if let Some(namespace) = namespace {
+        result.with_namespace(namespace);
+    }
+    if let Some(version) = purl.version() {
+        result.with_version(version.to_string());
+    }
+
+    // qualifiers and subpath are appended manually, to get a stable order
+    let mut result = result.to_string();
+
+    let qualifiers = purl
+        .qualifiers()
+        .iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(key, value)| (key.to_ascii_lowercase(), encode(value, ":/")))
+        .sorted()
+        .map(|(key, value)| format!("{key}={value}"))
+        .join("&");
+    if !qualifiers.is_empty() {
+        result.push('?');
+        result.push_str(&qualifiers);
+    }
+
+    if let Some(subpath) = purl.subpath() {
+        let subpath = subpath
+            .split('/')
+            .filter(|segment| !matches!(*segment, "" | "." | ".."))
+            .map(|segment| encode(segment, ""))
+            .join("/");
+        if !subpath.is_empty() {
+            result.push('#');
+            result.push_str(&subpath);
+        }
+    }
+
+    Some(result)
+}
+
+/// Percent-encode everything but unreserved characters, and the ones which are allowed.
+fn encode(value: &str, allowed: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() || "-._~".contains(c) || allowed.contains(c) {
+            result.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for b in c.encode_utf8(&mut buffer).bytes() {
+                result.push_str(&format!("%{b:02X}"));
+            }
+        }
+    }
+    result
+}
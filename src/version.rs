@@ -0,0 +1,544 @@
+//! Comparing versions, following the rules of the ecosystem of a package.
+//!
+//! The ecosystem is selected by the type of the package URL. Versions of other types, or versions
+//! which don't follow the rules of their ecosystem, are compared by their segments, comparing
+//! numeric segments as numbers. Those come after all versions following the rules, so that the
+//! order stays consistent when mixing both.
+
+use std::cmp::Ordering;
+
+/// Compare two versions of a package of the type (of the package URL).
+pub fn compare(ty: &str, a: &str, b: &str) -> Ordering {
+    match ty {
+        "maven" => maven(a, b),
+        "npm" | "cargo" | "golang" => parsed(a, b, semver_parts, semver),
+        "pypi" => parsed(a, b, pep440_parse, Pep440::cmp),
+        "rpm" => rpm(a, b),
+        _ => generic(a, b),
+    }
+}
+
+/// Compare two versions, parsing each of them once. Versions which can't be parsed come after all
+/// which can, and are compared by their segments.
+fn parsed<'a, T>(
+    a: &'a str,
+    b: &'a str,
+    parse: impl Fn(&'a str) -> Option<T>,
+    compare: impl Fn(&T, &T) -> Ordering,
+) -> Ordering {
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => compare(&a, &b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => generic(a, b),
+    }
+}
+
+/// Split a version into its alphanumeric segments, splitting between digits and letters too.
+fn segments(version: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut start = 0;
+    let mut digits = None;
+    for (n, c) in version.char_indices() {
+        let is_digit = c.is_ascii_digit();
+        if !c.is_alphanumeric() {
+            if start < n {
+                result.push(&version[start..n]);
+            }
+            start = n + c.len_utf8();
+            digits = None;
+        } else if digits.is_some() && digits != Some(is_digit) {
+            result.push(&version[start..n]);
+            start = n;
+            digits = Some(is_digit);
+        } else {
+            digits = Some(is_digit);
+        }
+    }
+    if start < version.len() {
+        result.push(&version[start..]);
+    }
+    result
+}
+
+/// Compare by segments, comparing numeric segments as numbers.
+fn generic(a: &str, b: &str) -> Ordering {
+    let a = segments(a);
+    let b = segments(b);
+
+    for (a, b) in a.iter().zip(&b) {
+        let result = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        };
+        if result != Ordering::Equal {
+            return result;
+        }
+    }
+
+    a.len().cmp(&b.len())
+}
+
+/// An item of a Maven version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum MavenItem {
+    Number(u64),
+    Qualifier(String),
+}
+
+impl MavenItem {
+    /// The rank of well-known qualifiers, unknown ones come after all of them.
+    fn rank(qualifier: &str) -> (usize, &str) {
+        match qualifier {
+            "alpha" => (0, ""),
+            "beta" => (1, ""),
+            "milestone" => (2, ""),
+            "rc" => (3, ""),
+            "snapshot" => (4, ""),
+            "" => (5, ""),
+            "sp" => (6, ""),
+            other => (7, other),
+        }
+    }
+
+    /// Compare with an item, `None` being the padding of a shorter version.
+    fn compare(&self, other: Option<&Self>) -> Ordering {
+        match (self, other) {
+            (Self::Number(a), Some(Self::Number(b))) => a.cmp(b),
+            (Self::Number(a), None) => a.cmp(&0),
+            (Self::Number(_), Some(Self::Qualifier(_))) => Ordering::Greater,
+            (Self::Qualifier(_), Some(Self::Number(_))) => Ordering::Less,
+            (Self::Qualifier(a), Some(Self::Qualifier(b))) => Self::rank(a).cmp(&Self::rank(b)),
+            (Self::Qualifier(a), None) => Self::rank(a).cmp(&Self::rank("")),
+        }
+    }
+}
+
+/// Parse a Maven version into its items, following the rules of `ComparableVersion`.
+fn maven_items(version: &str) -> Vec<MavenItem> {
+    let version = version.to_lowercase();
+    let segments = segments(&version);
+
+    let mut result = Vec::with_capacity(segments.len());
+    for (n, segment) in segments.iter().enumerate() {
+        let item = match segment.parse::<u64>() {
+            Ok(number) => MavenItem::Number(number),
+            Err(_) => {
+                // the short forms only count if directly followed by a number
+                let followed_by_number = segments
+                    .get(n + 1)
+                    .map(|next| next.parse::<u64>().is_ok())
+                    .unwrap_or_default();
+                let qualifier = match *segment {
+                    "a" if followed_by_number => "alpha",
+                    "b" if followed_by_number => "beta",
+                    "m" if followed_by_number => "milestone",
+                    "cr" => "rc",
+                    "ga" | "final" | "release" => "",
+                    other => other,
+                };
+                MavenItem::Qualifier(qualifier.to_string())
+            }
+        };
+        result.push(item);
+    }
+
+    // trailing "null" items don't count, so that 1.0.0 equals 1
+    while result.last().map(|item| item.compare(None)) == Some(Ordering::Equal) {
+        result.pop();
+    }
+
+    result
+}
+
+fn maven(a: &str, b: &str) -> Ordering {
+    let a = maven_items(a);
+    let b = maven_items(b);
+
+    for n in 0..a.len().max(b.len()) {
+        let result = match (a.get(n), b.get(n)) {
+            (Some(a), b) => a.compare(b),
+            (None, Some(b)) => b.compare(None).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if result != Ordering::Equal {
+            return result;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// A pre-release identifier of a semantic version.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Identifier<'a> {
+    // numeric identifiers have a lower precedence than alphanumeric ones
+    Numeric(u64),
+    Alphanumeric(&'a str),
+}
+
+/// The version core and the pre-release identifiers of a semantic version.
+type SemverParts<'a> = ([u64; 3], Vec<Identifier<'a>>);
+
+/// Parse a semantic version into the version core and the pre-release identifiers.
+fn semver_parts(version: &str) -> Option<SemverParts<'_>> {
+    let version = version.trim().trim_start_matches(['v', '=']);
+    // build metadata doesn't count
+    let version = version.split('+').next()?;
+    let (core, pre) = match version.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (version, None),
+    };
+
+    let mut numbers = [0; 3];
+    let mut parts = core.split('.');
+    for number in &mut numbers {
+        *number = parts.next()?.parse().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let pre = pre
+        .map(|pre| {
+            pre.split('.')
+                .map(|id| match id.parse::<u64>() {
+                    Ok(n) => Identifier::Numeric(n),
+                    Err(_) => Identifier::Alphanumeric(id),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some((numbers, pre))
+}
+
+fn semver((a_core, a_pre): &SemverParts, (b_core, b_pre): &SemverParts) -> Ordering {
+    a_core.cmp(b_core).then_with(|| {
+        // a pre-release has a lower precedence than the release
+        match (a_pre.is_empty(), b_pre.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a_pre.cmp(b_pre),
+        }
+    })
+}
+
+/// The parts of a PEP 440 version which are relevant for ordering.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Pep440 {
+    epoch: u64,
+    release: Vec<u64>,
+    /// `(0, …)` for dev-only releases, `(1, kind, n)` for pre-releases, `(2, …)` for releases
+    pre: (u8, u8, u64),
+    post: Option<u64>,
+    /// Dev releases come before the release they belong to
+    dev: (bool, u64),
+    local: String,
+}
+
+/// Parse a number from the start of the input, returning the rest.
+fn number(input: &str) -> (Option<u64>, &str) {
+    let end = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    (input[..end].parse().ok(), &input[end..])
+}
+
+/// Strip a separator (`.`, `-` or `_`) from the start of the input.
+fn separator(input: &str) -> &str {
+    input.strip_prefix(['.', '-', '_']).unwrap_or(input)
+}
+
+/// Strip one of the spellings of a label, followed by an optional number.
+fn label<'a>(input: &'a str, spellings: &[&str]) -> Option<(u64, &'a str)> {
+    let input = separator(input);
+    let rest = spellings
+        .iter()
+        .find_map(|spelling| input.strip_prefix(spelling))?;
+    let (n, rest) = number(separator(rest));
+    Some((n.unwrap_or_default(), rest))
+}
+
+fn pep440_parse(version: &str) -> Option<Pep440> {
+    let version = version.trim().to_lowercase();
+    let (version, local) = match version.split_once('+') {
+        Some((version, local)) => (version.to_string(), local.to_string()),
+        None => (version, String::new()),
+    };
+    let mut rest = version.trim_start_matches('v');
+
+    let mut epoch = 0;
+    if let Some((e, r)) = rest.split_once('!') {
+        epoch = e.parse().ok()?;
+        rest = r;
+    }
+
+    let mut release = vec![];
+    loop {
+        let (n, r) = number(rest);
+        release.push(n?);
+        match r.strip_prefix('.') {
+            Some(r) if r.starts_with(|c: char| c.is_ascii_digit()) => rest = r,
+            _ => {
+                rest = r;
+                break;
+            }
+        }
+    }
+    // trailing zeros don't count, so that releases compare as if padded with zeros
+    while release.len() > 1 && release.last() == Some(&0) {
+        release.pop();
+    }
+
+    // the longer spellings must come first
+    let mut pre = None;
+    for (kind, spellings) in [
+        (0, &["alpha", "a"][..]),
+        (1, &["beta", "b"][..]),
+        (2, &["preview", "pre", "rc", "c"][..]),
+    ] {
+        if let Some((n, r)) = label(rest, spellings) {
+            pre = Some((kind, n));
+            rest = r;
+            break;
+        }
+    }
+
+    let mut post = None;
+    if let Some((n, r)) = label(rest, &["post", "rev", "r"]) {
+        post = Some(n);
+        rest = r;
+    } else if let Some(r) = rest.strip_prefix('-') {
+        // the implicit post release, like 1.0-1
+        let (n, r) = number(r);
+        post = Some(n?);
+        rest = r;
+    }
+
+    let mut dev = None;
+    if let Some((n, r)) = label(rest, &["dev"]) {
+        dev = Some(n);
+        rest = r;
+    }
+
+    if !rest.is_empty() {
+        return None;
+    }
+
+    let pre = match (pre, post, dev) {
+        (Some((kind, n)), _, _) => (1, kind, n),
+        (None, None, Some(_)) => (0, 0, 0),
+        _ => (2, 0, 0),
+    };
+
+    Some(Pep440 {
+        epoch,
+        release,
+        pre,
+        post,
+        dev: match dev {
+            Some(n) => (false, n),
+            None => (true, 0),
+        },
+        local,
+    })
+}
+
+/// Compare two version strings the way `rpmvercmp` does.
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    let mut a = a;
+    let mut b = b;
+
+    loop {
+        // skip separators, but not the tilde and caret, which have a meaning
+        a = a.trim_start_matches(|c: char| !c.is_ascii_alphanumeric() && c != '~' && c != '^');
+        b = b.trim_start_matches(|c: char| !c.is_ascii_alphanumeric() && c != '~' && c != '^');
+
+        // a tilde sorts before everything, even the end of the version
+        match (a.strip_prefix('~'), b.strip_prefix('~')) {
+            (Some(ra), Some(rb)) => {
+                a = ra;
+                b = rb;
+                continue;
+            }
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => {}
+        }
+
+        // a caret sorts after the end of the version, but before everything else
+        match (a.strip_prefix('^'), b.strip_prefix('^')) {
+            (Some(ra), Some(rb)) => {
+                a = ra;
+                b = rb;
+                continue;
+            }
+            (Some(_), None) if b.is_empty() => return Ordering::Greater,
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) if a.is_empty() => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => {}
+        }
+
+        if a.is_empty() || b.is_empty() {
+            return a.len().cmp(&b.len());
+        }
+
+        let numeric = a.starts_with(|c: char| c.is_ascii_digit());
+        let split = |s: &str| -> usize {
+            s.find(|c: char| {
+                if numeric {
+                    !c.is_ascii_digit()
+                } else {
+                    !c.is_ascii_alphabetic()
+                }
+            })
+            .unwrap_or(s.len())
+        };
+
+        let (sa, ra) = a.split_at(split(a));
+        let (sb, rb) = b.split_at(split(b));
+
+        if sb.is_empty() {
+            // different types of segments, numeric ones are newer
+            return match numeric {
+                true => Ordering::Greater,
+                false => Ordering::Less,
+            };
+        }
+
+        let result = match numeric {
+            true => {
+                let sa = sa.trim_start_matches('0');
+                let sb = sb.trim_start_matches('0');
+                sa.len().cmp(&sb.len()).then_with(|| sa.cmp(sb))
+            }
+            false => sa.cmp(sb),
+        };
+        if result != Ordering::Equal {
+            return result;
+        }
+
+        a = ra;
+        b = rb;
+    }
+}
+
+/// Split an RPM version into epoch, version and release.
+fn evr(value: &str) -> (u64, &str, &str) {
+    let (epoch, rest) = match value.split_once(':') {
+        Some((epoch, rest)) => (epoch.parse().unwrap_or_default(), rest),
+        None => (0, value),
+    };
+    let (version, release) = rest.rsplit_once('-').unwrap_or((rest, ""));
+    (epoch, version, release)
+}
+
+fn rpm(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_version, a_release) = evr(a);
+    let (b_epoch, b_version, b_release) = evr(b);
+
+    a_epoch
+        .cmp(&b_epoch)
+        .then_with(|| rpmvercmp(a_version, b_version))
+        .then_with(|| rpmvercmp(a_release, b_release))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_order(ty: &str, versions: &[&str]) {
+        for pair in versions.windows(2) {
+            assert_eq!(
+                compare(ty, pair[0], pair[1]),
+                Ordering::Less,
+                "{ty}: {} < {}",
+                pair[0],
+                pair[1]
+            );
+            assert_eq!(
+                compare(ty, pair[1], pair[0]),
+                Ordering::Greater,
+                "{ty}: {} > {}",
+                pair[1],
+                pair[0]
+            );
+        }
+    }
+
+    #[test]
+    fn test_generic() {
+        assert_order("generic", &["1.9", "1.10", "1.10.1"]);
+        assert_eq!(compare("generic", "1.01", "1.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_maven() {
+        assert_order("maven", &["1.9", "1.10"]);
+        assert_order("maven", &["2.9.0", "2.16.2.Final"]);
+        assert_order(
+            "maven",
+            &["1.0-alpha1", "1.0-beta1", "1.0-rc1", "1.0", "1.0-sp1"],
+        );
+        assert_eq!(compare("maven", "1.0.0", "1"), Ordering::Equal);
+        assert_eq!(compare("maven", "1.0.Final", "1.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_semver() {
+        assert_order(
+            "npm",
+            &[
+                "1.0.0-alpha",
+                "1.0.0-alpha.1",
+                "1.0.0-beta",
+                "1.0.0",
+                "1.1.0",
+            ],
+        );
+        assert_eq!(compare("npm", "v1.0.0", "1.0.0+build"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_semver_mixed() {
+        // versions which aren't semantic versions come last, keeping the order transitive
+        assert_order("npm", &["1.0.0-alpha", "1.0.0", "1.0.0.1"]);
+        assert_eq!(compare("npm", "1.0.0-alpha", "1.0.0.1"), Ordering::Less);
+
+        let mut versions = vec!["1.0.0.1", "2.0.0", "1.0.0", "1.0.0-alpha", "0.9"];
+        versions.sort_by(|a, b| compare("npm", a, b));
+        assert_eq!(
+            versions,
+            vec!["1.0.0-alpha", "1.0.0", "2.0.0", "0.9", "1.0.0.1"]
+        );
+    }
+
+    #[test]
+    fn test_pep440() {
+        assert_order(
+            "pypi",
+            &[
+                "1.0.dev1",
+                "1.0a1",
+                "1.0b1",
+                "1.0rc1",
+                "1.0",
+                "1.0.post1.dev1",
+                "1.0.post1",
+                "1.1",
+                "1!0.1",
+            ],
+        );
+        assert_eq!(compare("pypi", "1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare("pypi", "1.0-1", "1.0.post1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_rpm() {
+        assert_order("rpm", &["1.0~rc1", "1.0", "1.0^git1", "1.0.1"]);
+        assert_order("rpm", &["1.0-1", "1.0-2", "1.0-10", "1:0.9-1"]);
+    }
+}